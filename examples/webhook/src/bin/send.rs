@@ -1,27 +1,81 @@
+use std::process;
+
 use dbl::types::{Webhook, WebhookType};
-use reqwest::header::AUTHORIZATION;
+use dbl::webhook::send::Sender;
+
+const USAGE: &str = "\
+Usage: send [OPTIONS]
+
+Options:
+    --url <URL>                 Webhook receiver [default: http://localhost:3030/dbl/webhook]
+    --secret <SECRET>           Secret sent in the Authorization header [default: mywebhook]
+    --signing-secret <SECRET>   Sign the payload instead of sending the plain secret
+    --bot <ID>                  Bot id [default: 1234]
+    --user <ID>                 User id [default: 2345]
+    --type <upvote|test>        Vote type [default: test]
+    --weekend                   Mark the vote as weekend vote
+    --query <QUERY>             Query string of the vote link, e.g. ?ref=home
+    --count <N>                 Number of deliveries [default: 1]
+    --concurrency <N>           Number of concurrent deliveries [default: 1]";
 
 #[tokio::main]
 async fn main() {
-    let url = "http://localhost:3030/dbl/webhook";
-    let secret = "mywebhook";
-
-    let data = Webhook {
-        bot: 1234.into(),
-        user: 2345.into(),
-        kind: WebhookType::Test,
-        is_weekend: false,
-        query: None,
-    };
+    let mut url = String::from("http://localhost:3030/dbl/webhook");
+    let mut secret = String::from("mywebhook");
+    let mut signing_secret = None;
+    let mut count = 1;
+    let mut concurrency = 1;
 
-    let resp = reqwest::Client::new()
-        .post(url)
-        .header(AUTHORIZATION, secret)
-        .json(&data)
-        .send()
-        .await;
+    let mut data = Webhook::new(1234, 2345, WebhookType::Test);
 
-    if let Err(e) = resp.map(|resp| resp.error_for_status()) {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().unwrap_or_else(|| usage());
+        match &*arg {
+            "--url" => url = value(),
+            "--secret" => secret = value(),
+            "--signing-secret" => signing_secret = Some(value()),
+            "--bot" => data.bot = parse(&value()).into(),
+            "--user" => data.user = parse(&value()).into(),
+            "--type" => {
+                data.kind = WebhookType::from(&*value());
+            }
+            "--weekend" => data.is_weekend = true,
+            "--query" => data.query = Some(value()),
+            "--count" => count = parse(&value()) as usize,
+            "--concurrency" => concurrency = parse(&value()) as usize,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => usage(),
+        }
+    }
+
+    let sender = Sender::new(url);
+    let sender = match signing_secret {
+        Some(signing_secret) => sender.signed(signing_secret),
+        None => sender.secret(secret),
+    };
+
+    let report = sender.send_many(&data, count, concurrency).await;
+    println!(
+        "{} of {} deliveries succeeded in {:?}",
+        report.succeeded, count, report.elapsed
+    );
+    for e in &report.failed {
         eprintln!("{}", e);
     }
+    if !report.failed.is_empty() {
+        process::exit(1);
+    }
+}
+
+fn parse(value: &str) -> u64 {
+    value.parse().unwrap_or_else(|_| usage())
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2);
 }
@@ -0,0 +1,564 @@
+//! Aggregation of shard stats across processes.
+//!
+//! Bots with shards spread across several processes only know the server counts of their own
+//! shards. The [`Aggregator`] collects the server counts reported by the worker processes and
+//! posts one consolidated update with [`Client::update_stats`] on a schedule. The update is
+//! only posted once all shards reported, a partial sum is never posted as the total.
+//!
+//! The aggregator speaks a minimal HTTP protocol over a TCP or a Unix domain socket. Workers
+//! report the server count of a shard with a `POST /shards` request and a JSON body:
+//!
+//! ```json
+//! {"shard_id": 0, "shard_count": 4, "server_count": 1234}
+//! ```
+//!
+//! The [`Reporter`] sends these reports over HTTP.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use dbl::aggregator::Aggregator;
+//! use dbl::Client;
+//! use tokio::net::TcpListener;
+//!
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = Client::new("token".into())?;
+//! let aggregator = Aggregator::new(client, 565_030_624_499_466_240).shard_count(4);
+//!
+//! let listener = TcpListener::bind("127.0.0.1:3031").await?;
+//! tokio::spawn(aggregator.clone().serve(listener));
+//!
+//! aggregator
+//!     .run(|result| {
+//!         if let Err(e) = result {
+//!             eprintln!("failed to post stats: {}", e);
+//!         }
+//!     })
+//!     .await;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! In the worker processes:
+//!
+//! ```no_run
+//! use dbl::aggregator::Reporter;
+//!
+//! # async fn run() -> Result<(), dbl::Error> {
+//! let reporter = Reporter::new("http://127.0.0.1:3031");
+//! reporter.report(0, 4, 1234).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use reqwest::Client as ReqwestClient;
+use serde::{Deserialize, Serialize};
+use tokio::io::{
+    AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader,
+};
+use tokio::net::TcpListener;
+use tokio::time::Instant;
+
+use crate::autopost;
+use crate::error;
+use crate::types::{BotId, ShardStats, ShardStatsBuilder, ShardStatsError};
+use crate::{Client, Error};
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(30 * 60);
+const DEFAULT_STALE_AFTER: Duration = Duration::from_secs(10 * 60);
+const MAX_BODY_SIZE: usize = 16 * 1024;
+const MAX_LINE_SIZE: usize = 8 * 1024;
+const MAX_HEADERS: usize = 32;
+
+/// Server count of a shard reported by a worker process.
+#[derive(Deserialize, Serialize)]
+struct ShardReport {
+    shard_id: u64,
+    shard_count: u64,
+    server_count: u64,
+}
+
+struct Shard {
+    server_count: u64,
+    shard_count: u64,
+    updated: Instant,
+}
+
+/// Collector of per-shard server counts that posts consolidated stats.
+///
+/// The stats are only built if every shard reported within the
+/// [`stale_after`](Aggregator::stale_after) window. Otherwise the stale or missing shards are
+/// returned as [`ShardStatsError::StaleShards`] or [`ShardStatsError::MissingShards`] and
+/// nothing is posted. Shards of workers that were shut down for good are dropped with
+/// [`remove_shard`](Aggregator::remove_shard). Cloned instances share the same reports.
+#[derive(Clone)]
+pub struct Aggregator {
+    client: Client,
+    bot: BotId,
+    interval: Duration,
+    stale_after: Duration,
+    shard_count: Option<u64>,
+    shards: Arc<Mutex<HashMap<u64, Shard>>>,
+}
+
+impl Aggregator {
+    /// Constructs a new `Aggregator` for the stats of `bot`.
+    pub fn new<T: Into<BotId>>(client: Client, bot: T) -> Self {
+        Aggregator {
+            client,
+            bot: bot.into(),
+            interval: DEFAULT_INTERVAL,
+            stale_after: DEFAULT_STALE_AFTER,
+            shard_count: None,
+            shards: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Sets the interval of [`run`](Aggregator::run). Defaults to 30 minutes.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets the time after which a shard without reports is stale. Defaults to 10 minutes.
+    pub fn stale_after(mut self, stale_after: Duration) -> Self {
+        self.stale_after = stale_after;
+        self
+    }
+
+    /// Sets the total number of shards.
+    ///
+    /// Defaults to the highest shard count of the reports.
+    pub fn shard_count(mut self, count: u64) -> Self {
+        self.shard_count = Some(count);
+        self
+    }
+
+    /// Records the server count of a shard.
+    ///
+    /// A report with a different shard count than the recorded shards, e.g. after resharding,
+    /// replaces the reports of the previous shard count.
+    pub fn report(
+        &self,
+        shard_id: u64,
+        shard_count: u64,
+        server_count: u64,
+    ) -> Result<(), ShardStatsError> {
+        self.report_at(shard_id, shard_count, server_count, Instant::now())
+    }
+
+    fn report_at(
+        &self,
+        shard_id: u64,
+        shard_count: u64,
+        server_count: u64,
+        now: Instant,
+    ) -> Result<(), ShardStatsError> {
+        let shard_count = self.shard_count.unwrap_or(shard_count);
+        let stats = ShardStats::Shard {
+            server_count,
+            shard_id,
+            shard_count,
+        };
+        stats.validate()?;
+
+        let shard = Shard {
+            server_count,
+            shard_count,
+            updated: now,
+        };
+        let mut shards = self.shards.lock().unwrap();
+        shards.retain(|_, shard| shard.shard_count == shard_count);
+        shards.insert(shard_id, shard);
+        Ok(())
+    }
+
+    /// Removes the report of a shard, e.g. of a worker that was shut down for good.
+    ///
+    /// Returns the last reported server count of the shard.
+    pub fn remove_shard(&self, shard_id: u64) -> Option<u64> {
+        let mut shards = self.shards.lock().unwrap();
+        shards.remove(&shard_id).map(|shard| shard.server_count)
+    }
+
+    /// Removes the reports of all shards.
+    pub fn clear(&self) {
+        self.shards.lock().unwrap().clear();
+    }
+
+    /// Returns the ids of the shards without a report within the `stale_after` window.
+    pub fn stale_shards(&self) -> Vec<u64> {
+        self.stale_shards_at(Instant::now())
+    }
+
+    fn stale_shards_at(&self, now: Instant) -> Vec<u64> {
+        let shards = self.shards.lock().unwrap();
+        let mut stale = shards
+            .iter()
+            .filter(|(_, shard)| now.duration_since(shard.updated) > self.stale_after)
+            .map(|(&id, _)| id)
+            .collect::<Vec<_>>();
+        stale.sort_unstable();
+        stale
+    }
+
+    /// Builds the consolidated stats from the reports of all shards.
+    pub fn stats(&self) -> Result<ShardStats, ShardStatsError> {
+        self.stats_at(Instant::now())
+    }
+
+    fn stats_at(&self, now: Instant) -> Result<ShardStats, ShardStatsError> {
+        let stale = self.stale_shards_at(now);
+        if !stale.is_empty() {
+            return Err(ShardStatsError::StaleShards(stale));
+        }
+
+        let shards = self.shards.lock().unwrap();
+        let shard_count = self
+            .shard_count
+            .or_else(|| shards.values().map(|s| s.shard_count).max());
+
+        let mut builder = ShardStatsBuilder::new();
+        if let Some(count) = shard_count {
+            builder = builder.shard_count(count);
+        }
+        for (&id, shard) in shards.iter() {
+            builder.insert(id, shard.server_count);
+        }
+        builder.build_shards()
+    }
+
+    /// Posts the consolidated stats and returns them.
+    pub async fn post(&self) -> Result<ShardStats, Error> {
+        let stats = self.stats().map_err(Error::InvalidStats)?;
+        self.client.update_stats(self.bot, stats.clone()).await?;
+        Ok(stats)
+    }
+
+    /// Posts the consolidated stats at the configured interval.
+    ///
+    /// The callback is called with the result of every post, including the stale or missing
+    /// shards of skipped posts. The first post happens after the first interval to give the
+    /// workers time to report.
+    pub async fn run<F>(&self, callback: F)
+    where
+        F: FnMut(Result<ShardStats, Error>),
+    {
+        autopost::run(self.interval, || self.post(), callback).await
+    }
+
+    /// Accepts reports of the worker processes on a TCP socket.
+    pub async fn serve(self, listener: TcpListener) -> io::Result<()> {
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let aggregator = self.clone();
+            tokio::spawn(async move { aggregator.handle(stream).await });
+        }
+    }
+
+    /// Accepts reports of the worker processes on a Unix domain socket.
+    #[cfg(unix)]
+    pub async fn serve_unix(self, listener: tokio::net::UnixListener) -> io::Result<()> {
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let aggregator = self.clone();
+            tokio::spawn(async move { aggregator.handle(stream).await });
+        }
+    }
+
+    async fn handle<S>(&self, stream: S) -> io::Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let mut reader = BufReader::new(stream);
+        let (status, body) = self.read_request(&mut reader).await?;
+
+        let resp = format!(
+            "HTTP/1.1 {}\r\nconnection: close\r\ncontent-length: {}\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+        let stream = reader.get_mut();
+        stream.write_all(resp.as_bytes()).await?;
+        stream.shutdown().await
+    }
+
+    async fn read_request<R>(&self, reader: &mut R) -> io::Result<(&'static str, String)>
+    where
+        R: AsyncBufRead + Unpin,
+    {
+        let mut request_line = String::new();
+        if !read_line(reader, &mut request_line).await? {
+            return Ok(("414 URI Too Long", String::new()));
+        }
+        let mut len = 0;
+        let mut headers = 0;
+        loop {
+            let mut line = String::new();
+            if !read_line(reader, &mut line).await? {
+                return Ok(("431 Request Header Fields Too Large", String::new()));
+            }
+            if line.trim_end().is_empty() {
+                break;
+            }
+            if headers == MAX_HEADERS {
+                return Ok(("431 Request Header Fields Too Large", String::new()));
+            }
+            headers += 1;
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    len = value.trim().parse().unwrap_or(0);
+                }
+            }
+        }
+
+        let mut parts = request_line.split_whitespace();
+        let resp = match (parts.next(), parts.next()) {
+            (Some("POST"), Some("/shards")) if len <= MAX_BODY_SIZE => {
+                let mut body = vec![0; len];
+                reader.read_exact(&mut body).await?;
+                self.handle_report(&body)
+            }
+            (Some("POST"), Some("/shards")) => ("413 Payload Too Large", String::new()),
+            (_, Some("/shards")) => ("405 Method Not Allowed", String::new()),
+            _ => ("404 Not Found", String::new()),
+        };
+        Ok(resp)
+    }
+
+    fn handle_report(&self, body: &[u8]) -> (&'static str, String) {
+        let report = match serde_json::from_slice::<ShardReport>(body) {
+            Ok(report) => report,
+            Err(e) => return ("400 Bad Request", e.to_string()),
+        };
+        match self.report(report.shard_id, report.shard_count, report.server_count) {
+            Ok(()) => ("204 No Content", String::new()),
+            Err(e) => ("400 Bad Request", e.to_string()),
+        }
+    }
+}
+
+/// Reads a line of at most `MAX_LINE_SIZE` bytes. Returns `false` if the line is longer.
+async fn read_line<R>(reader: &mut R, line: &mut String) -> io::Result<bool>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let limit = MAX_LINE_SIZE as u64 + 1;
+    let len = (&mut *reader).take(limit).read_line(line).await?;
+    Ok(len <= MAX_LINE_SIZE)
+}
+
+/// Sender of shard reports to an [`Aggregator`] over HTTP.
+#[derive(Clone, Debug)]
+pub struct Reporter {
+    client: ReqwestClient,
+    url: String,
+}
+
+impl Reporter {
+    /// Constructs a new `Reporter` for the aggregator at `url`, e.g. `http://127.0.0.1:3031`.
+    pub fn new<T: Into<String>>(url: T) -> Self {
+        Reporter::new_with_client(ReqwestClient::new(), url)
+    }
+
+    /// Constructs a new `Reporter` with a `reqwest` client.
+    pub fn new_with_client<T: Into<String>>(client: ReqwestClient, url: T) -> Self {
+        Reporter {
+            client,
+            url: url.into(),
+        }
+    }
+
+    /// Reports the server count of a shard.
+    pub async fn report(
+        &self,
+        shard_id: u64,
+        shard_count: u64,
+        server_count: u64,
+    ) -> Result<(), Error> {
+        let report = ShardReport {
+            shard_id,
+            shard_count,
+            server_count,
+        };
+        let url = format!("{}/shards", self.url.trim_end_matches('/'));
+        let resp = self.client.post(url).json(&report).send().await;
+        let resp = resp.map_err(error::from)?;
+        resp.error_for_status().map(|_| ()).map_err(error::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, Redirect};
+
+    #[tokio::test]
+    async fn stale_shards() {
+        let client = Client::new("token".into()).unwrap();
+        let aggregator = Aggregator::new(client, 1).stale_after(Duration::from_secs(60));
+        let now = Instant::now();
+
+        aggregator.report_at(0, 2, 10, now).unwrap();
+        aggregator.report_at(1, 2, 20, now).unwrap();
+        let stats = aggregator.stats_at(now).unwrap();
+        assert_eq!(
+            stats,
+            ShardStats::Shards {
+                shards: vec![10, 20]
+            }
+        );
+
+        let later = now + Duration::from_secs(90);
+        aggregator.report_at(1, 2, 25, later).unwrap();
+        assert_eq!(aggregator.stale_shards_at(later), [0]);
+        let err = ShardStatsError::StaleShards(vec![0]);
+        assert_eq!(aggregator.stats_at(later), Err(err));
+
+        let err = aggregator.report_at(2, 2, 30, later).unwrap_err();
+        assert!(matches!(err, ShardStatsError::InvalidShardId { .. }));
+
+        assert_eq!(aggregator.remove_shard(0), Some(10));
+        assert!(aggregator.stale_shards_at(later).is_empty());
+        aggregator.report_at(0, 2, 15, later).unwrap();
+        let stats = aggregator.stats_at(later).unwrap();
+        assert_eq!(
+            stats,
+            ShardStats::Shards {
+                shards: vec![15, 25]
+            }
+        );
+
+        aggregator.clear();
+        assert_eq!(aggregator.stats_at(later), Err(ShardStatsError::Empty));
+    }
+
+    #[tokio::test]
+    async fn reshard() {
+        let client = Client::new("token".into()).unwrap();
+        let aggregator = Aggregator::new(client, 1);
+        let now = Instant::now();
+
+        for id in 0..4 {
+            aggregator.report_at(id, 4, 10, now).unwrap();
+        }
+        aggregator.report_at(0, 2, 15, now).unwrap();
+        let err = ShardStatsError::MissingShards(vec![1]);
+        assert_eq!(aggregator.stats_at(now), Err(err));
+
+        aggregator.report_at(1, 2, 25, now).unwrap();
+        let stats = aggregator.stats_at(now).unwrap();
+        assert_eq!(
+            stats,
+            ShardStats::Shards {
+                shards: vec![15, 25]
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_shards() {
+        let client = Client::new("token".into()).unwrap();
+        let aggregator = Aggregator::new(client, 1).shard_count(3);
+
+        aggregator.report(1, 3, 20).unwrap();
+        let err = aggregator.post().await.unwrap_err();
+        assert!(matches!(
+            err,
+            Error::InvalidStats(ShardStatsError::MissingShards(ref shards)) if shards == &[0, 2]
+        ));
+    }
+
+    #[tokio::test]
+    async fn request_limits() {
+        let client = Client::new("token".into()).unwrap();
+        let aggregator = Aggregator::new(client, 1);
+
+        let req = format!(
+            "POST /shards HTTP/1.1\r\nx-long: {}\r\n\r\n",
+            "a".repeat(MAX_LINE_SIZE)
+        );
+        let (status, _) = aggregator.read_request(&mut req.as_bytes()).await.unwrap();
+        assert_eq!(status, "431 Request Header Fields Too Large");
+
+        let req = format!(
+            "POST /shards HTTP/1.1\r\n{}\r\n",
+            "x-header: a\r\n".repeat(MAX_HEADERS + 1)
+        );
+        let (status, _) = aggregator.read_request(&mut req.as_bytes()).await.unwrap();
+        assert_eq!(status, "431 Request Header Fields Too Large");
+
+        let req = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_LINE_SIZE));
+        let (status, _) = aggregator.read_request(&mut req.as_bytes()).await.unwrap();
+        assert_eq!(status, "414 URI Too Long");
+
+        let req = "GET /shards HTTP/1.1\r\nhost: localhost\r\n\r\n";
+        let (status, _) = aggregator.read_request(&mut req.as_bytes()).await.unwrap();
+        assert_eq!(status, "405 Method Not Allowed");
+    }
+
+    #[tokio::test]
+    async fn serve_and_post() {
+        let (port, server) = test_util::serve(vec![("200 OK", "")]);
+        let client = Client::new("token".into())
+            .unwrap()
+            .with_hook(Redirect(port));
+        let aggregator = Aggregator::new(client, 1);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(aggregator.clone().serve(listener));
+
+        let reporter = Reporter::new(format!("http://{}", addr));
+        reporter.report(1, 2, 20).await.unwrap();
+        reporter.report(0, 2, 10).await.unwrap();
+        let err = reporter.report(2, 2, 30).await.unwrap_err();
+        assert_eq!(err.status(), Some(reqwest::StatusCode::BAD_REQUEST));
+
+        let stats = aggregator.post().await.unwrap();
+        assert_eq!(
+            stats,
+            ShardStats::Shards {
+                shards: vec![10, 20]
+            }
+        );
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests[0].request_line, "POST /api/bots/1/stats HTTP/1.1");
+        assert_eq!(requests[0].body, br#"{"shards":[10,20]}"#);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn serve_unix() {
+        let path = std::env::temp_dir().join(format!("dbl-aggregator-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let client = Client::new("token".into()).unwrap();
+        let aggregator = Aggregator::new(client, 1).shard_count(1);
+
+        let listener = tokio::net::UnixListener::bind(&path).unwrap();
+        tokio::spawn(aggregator.clone().serve_unix(listener));
+
+        let body = r#"{"shard_id":0,"shard_count":1,"server_count":10}"#;
+        let req = format!(
+            "POST /shards HTTP/1.1\r\ncontent-length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let mut stream = tokio::net::UnixStream::connect(&path).await.unwrap();
+        stream.write_all(req.as_bytes()).await.unwrap();
+        let mut resp = String::new();
+        stream.read_to_string(&mut resp).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(resp.starts_with("HTTP/1.1 204 No Content"));
+        let stats = aggregator.stats().unwrap();
+        assert_eq!(stats, ShardStats::Shards { shards: vec![10] });
+    }
+}
@@ -0,0 +1,24 @@
+//! Loop of the periodic stats posts.
+
+use std::future::Future;
+use std::time::Duration;
+
+use tokio::time::{self, Instant, MissedTickBehavior};
+
+/// Calls `post` every `period` and passes the results to `callback`.
+///
+/// The first post happens after the first period. Delayed posts don't catch up on the missed
+/// ticks.
+pub(crate) async fn run<T, F, Fut, C>(period: Duration, mut post: F, mut callback: C)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = T>,
+    C: FnMut(T),
+{
+    let mut interval = time::interval_at(Instant::now() + period, period);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        callback(post().await);
+    }
+}
@@ -0,0 +1,162 @@
+//! URL Builders for avatars on the Discord CDN and top.gg banners.
+//!
+//! # Example
+//!
+//! ```
+//! use dbl::cdn::ImageFormat;
+//! # use dbl::types::User;
+//! # let user: User = serde_json::from_str(
+//! #     r#"{"id":"1","username":"foo","discriminator":"0","avatar":"a_1234"}"#,
+//! # ).unwrap();
+//!
+//! let url = user.avatar_url(128, ImageFormat::Gif);
+//! assert_eq!(url.as_str(), "https://cdn.discordapp.com/avatars/1/a_1234.gif?size=128");
+//! ```
+
+use url::Url;
+
+use crate::types::{Bot, DetailedUser, User};
+
+const CDN_URL: &str = "https://cdn.discordapp.com";
+
+/// Image format of the CDN URLs.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    WebP,
+    /// Only available for animated avatars, static avatars fall back to `Png`.
+    Gif,
+}
+
+impl ImageFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Jpeg => "jpg",
+            ImageFormat::WebP => "webp",
+            ImageFormat::Gif => "gif",
+        }
+    }
+}
+
+/// Returns the URL of the avatar or the default avatar if the user has no custom avatar.
+///
+/// The size is rounded to the next power of two between 16 and 4096.
+fn avatar_url(
+    id: u64,
+    avatar: Option<&str>,
+    discriminator: &str,
+    size: u16,
+    format: ImageFormat,
+) -> Url {
+    let hash = match avatar {
+        Some(hash) if !hash.is_empty() => hash,
+        _ => return default_avatar_url(id, discriminator),
+    };
+    let format = match format {
+        ImageFormat::Gif if !hash.starts_with("a_") => ImageFormat::Png,
+        format => format,
+    };
+    let size = size.clamp(16, 4096).next_power_of_two();
+    let url = format!(
+        "{}/avatars/{}/{}.{}?size={}",
+        CDN_URL,
+        id,
+        hash,
+        format.extension(),
+        size
+    );
+    Url::parse(&url).expect("valid cdn url")
+}
+
+/// Returns the URL of the default avatar.
+///
+/// Users of the new username system without discriminator (`"0"`) get a default avatar
+/// derived from their id.
+fn default_avatar_url(id: u64, discriminator: &str) -> Url {
+    let index = match discriminator.parse::<u64>() {
+        Ok(discriminator) if discriminator != 0 => discriminator % 5,
+        _ => (id >> 22) % 6,
+    };
+    let url = format!("{}/embed/avatars/{}.png", CDN_URL, index);
+    Url::parse(&url).expect("valid cdn url")
+}
+
+macro_rules! impl_avatar {
+    ($($type:ty),*) => {
+        $(
+            impl $type {
+                /// Returns the avatar URL on the Discord CDN.
+                ///
+                /// Falls back to the default avatar if no custom avatar is set. The size is
+                /// rounded to the next power of two between 16 and 4096.
+                pub fn avatar_url(&self, size: u16, format: ImageFormat) -> Url {
+                    let avatar = self.avatar.as_deref();
+                    avatar_url(self.id.0, avatar, &self.discriminator, size, format)
+                }
+
+                /// Returns the default avatar URL on the Discord CDN.
+                pub fn default_avatar_url(&self) -> Url {
+                    default_avatar_url(self.id.0, &self.discriminator)
+                }
+            }
+        )*
+    };
+}
+
+impl_avatar!(Bot, DetailedUser, User);
+
+impl DetailedUser {
+    /// Returns the URL of the user's top.gg profile banner.
+    pub fn banner_url(&self) -> Option<Url> {
+        self.banner
+            .as_deref()
+            .and_then(|banner| Url::parse(banner).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn avatar_formats() {
+        let url = avatar_url(1, Some("a_1"), "0", 100, ImageFormat::Gif);
+        assert_eq!(
+            url.as_str(),
+            "https://cdn.discordapp.com/avatars/1/a_1.gif?size=128"
+        );
+
+        let url = avatar_url(1, Some("1"), "0", 5000, ImageFormat::Gif);
+        assert_eq!(
+            url.as_str(),
+            "https://cdn.discordapp.com/avatars/1/1.png?size=4096"
+        );
+
+        let url = avatar_url(1, Some("a_1"), "0", 64, ImageFormat::WebP);
+        assert_eq!(
+            url.as_str(),
+            "https://cdn.discordapp.com/avatars/1/a_1.webp?size=64"
+        );
+    }
+
+    #[test]
+    fn default_avatars() {
+        let url = avatar_url(1, None, "1337", 64, ImageFormat::Png);
+        assert_eq!(
+            url.as_str(),
+            "https://cdn.discordapp.com/embed/avatars/2.png"
+        );
+
+        let id = 565_030_624_499_466_240;
+        let url = default_avatar_url(id, "0");
+        assert_eq!(
+            url.as_str(),
+            format!(
+                "https://cdn.discordapp.com/embed/avatars/{}.png",
+                (id >> 22) % 6
+            )
+        );
+    }
+}
@@ -0,0 +1,669 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::stream::{self, Stream, StreamExt, TryStreamExt};
+use reqwest::header::AUTHORIZATION;
+use reqwest::Client as ReqwestClient;
+use reqwest::{Method, StatusCode};
+use tokio::time::{self, Instant};
+use url::Url;
+
+use crate::error;
+use crate::hook::{self, Hook};
+use crate::types::*;
+use crate::Error;
+
+const REVIEWS_PAGE_SIZE: u64 = 50;
+
+/// Version of the top.gg API used by the [`Client`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ApiVersion {
+    /// The legacy `/api/bots` routes.
+    #[default]
+    Legacy,
+    /// The v1 `/api/v1/projects` routes.
+    ///
+    /// [`Client::update_stats`], [`Client::has_voted`] and [`Client::vote_status`] use the v1
+    /// routes of the token's project, the other methods fall back to the legacy routes. The
+    /// token is sent as bearer token to the v1 routes and as is to the legacy routes.
+    V1,
+}
+
+/// Endpoint interface to Discord Bot List API.
+#[derive(Clone)]
+pub struct Client {
+    client: ReqwestClient,
+    token: String,
+    version: ApiVersion,
+    hooks: Vec<Arc<dyn Hook>>,
+    ratelimit: Arc<Mutex<Option<Instant>>>,
+}
+
+impl Client {
+    /// Constructs a new `Client`.
+    pub fn new(token: String) -> Result<Self, Error> {
+        let client = ReqwestClient::builder().build().map_err(error::from)?;
+        Ok(Client::new_with_client(client, token))
+    }
+
+    /// Constructs a new `Client` with a `reqwest` client.
+    pub fn new_with_client(client: ReqwestClient, token: String) -> Self {
+        Client {
+            client,
+            token,
+            version: ApiVersion::default(),
+            hooks: Vec::new(),
+            ratelimit: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Sets the version of the top.gg API. Defaults to [`ApiVersion::Legacy`].
+    pub fn with_api_version(mut self, version: ApiVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Returns the version of the top.gg API used by the client.
+    pub fn api_version(&self) -> ApiVersion {
+        self.version
+    }
+
+    /// Adds a [`Hook`] that is called before each request is sent and after each response is
+    /// received.
+    pub fn with_hook<H>(mut self, hook: H) -> Self
+    where
+        H: Hook + 'static,
+    {
+        self.hooks.push(Arc::new(hook));
+        self
+    }
+
+    /// Get information about a specific bot.
+    pub async fn get<T>(&self, bot: T) -> Result<Bot, Error>
+    where
+        T: Into<BotId>,
+    {
+        let url = api!("/bots/{}", bot.into());
+        get(self, url).await
+    }
+
+    /// Get information about many bots with at most `concurrency` requests in flight.
+    ///
+    /// A failed request only fails the result of the affected bot.
+    pub async fn get_many<I>(
+        &self,
+        bots: I,
+        concurrency: usize,
+    ) -> HashMap<BotId, Result<Bot, Error>>
+    where
+        I: IntoIterator,
+        I::Item: Into<BotId>,
+    {
+        let bots = bots.into_iter().map(Into::into);
+        batch(bots, concurrency, |bot| self.get(bot)).await
+    }
+
+    /// Search for bots.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use dbl::types::Filter;
+    ///
+    /// let filter = Filter::new().search("lib:serenity foobar");
+    /// ```
+    pub async fn search(&self, filter: &Filter) -> Result<Listing, Error> {
+        let url = Url::parse_with_params(api!("/bots"), &filter.0).map_err(Error::Url)?;
+        get(self, url.to_string()).await
+    }
+
+    /// Get the stats of a bot.
+    pub async fn stats<T>(&self, bot: T) -> Result<Stats, Error>
+    where
+        T: Into<BotId>,
+    {
+        let url = api!("/bots/{}/stats", bot.into());
+        get(self, url).await
+    }
+
+    /// Update the stats of a bot.
+    ///
+    /// Returns [`Error::InvalidStats`] without sending a request if the stats are inconsistent
+    /// or if [`ShardStats::Shard`] is posted with [`ApiVersion::V1`], which only accepts the
+    /// totals of the bot.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use dbl::types::ShardStats;
+    ///
+    /// let new_stats = ShardStats::Cumulative {
+    ///     server_count: 1234,
+    ///     shard_count: None,
+    /// };
+    /// ```
+    pub async fn update_stats<T>(&self, bot: T, stats: ShardStats) -> Result<(), Error>
+    where
+        T: Into<BotId>,
+    {
+        stats.validate().map_err(Error::InvalidStats)?;
+        match self.version {
+            ApiVersion::Legacy => {
+                let url = api!("/bots/{}/stats", bot.into());
+                post(self, url, Some(stats)).await
+            }
+            ApiVersion::V1 => {
+                let url = api_v1!("/projects/@me/metrics").to_string();
+                let metrics = v1::Metrics::try_from(stats).map_err(Error::InvalidStats)?;
+                post(self, url, Some(metrics)).await
+            }
+        }
+    }
+
+    /// Get the last 1000 votes for a bot.
+    pub async fn votes<T>(&self, bot: T) -> Result<Vec<User>, Error>
+    where
+        T: Into<BotId>,
+    {
+        let url = api!("/bots/{}/votes", bot.into());
+        get(self, url).await
+    }
+
+    /// Check if a user has voted for a bot in the past 24 hours.
+    pub async fn has_voted<T, U>(&self, bot: T, user: U) -> Result<bool, Error>
+    where
+        T: Into<BotId>,
+        U: Into<UserId>,
+    {
+        Ok(self.vote_status(bot, user).await?.voted)
+    }
+
+    /// Get the vote status of a user for a bot.
+    ///
+    /// The legacy API only reports whether the user has voted, the other fields of the
+    /// [`VoteStatus`] are only set with the [v1 API](ApiVersion::V1).
+    pub async fn vote_status<T, U>(&self, bot: T, user: U) -> Result<VoteStatus, Error>
+    where
+        T: Into<BotId>,
+        U: Into<UserId>,
+    {
+        match self.version {
+            ApiVersion::Legacy => {
+                let bot = bot.into();
+                let user = user.into();
+                let url = api!("/bots/{}/check?userId={}", bot, user);
+                let v: UserVoted = get(self, url).await?;
+                Ok(VoteStatus {
+                    voted: v.voted > 0,
+                    ..VoteStatus::default()
+                })
+            }
+            ApiVersion::V1 => {
+                let status = match self.project_vote(user).await? {
+                    Some(vote) => VoteStatus {
+                        voted: true,
+                        voted_at: Some(vote.created_at),
+                        is_weekend: Some(vote.weight > 1),
+                        next_vote_at: Some(vote.expires_at),
+                    },
+                    None => VoteStatus::default(),
+                };
+                Ok(status)
+            }
+        }
+    }
+
+    /// Get the vote status of many users for a bot with at most `concurrency` requests in
+    /// flight.
+    ///
+    /// A failed request only fails the result of the affected user.
+    pub async fn vote_statuses<T, I>(
+        &self,
+        bot: T,
+        users: I,
+        concurrency: usize,
+    ) -> HashMap<UserId, Result<VoteStatus, Error>>
+    where
+        T: Into<BotId>,
+        I: IntoIterator,
+        I::Item: Into<UserId>,
+    {
+        let bot = bot.into();
+        let users = users.into_iter().map(Into::into);
+        batch(users, concurrency, |user| self.vote_status(bot, user)).await
+    }
+
+    /// Get information about the project of the token.
+    ///
+    /// Always uses the [v1 API](ApiVersion::V1).
+    pub async fn project(&self) -> Result<v1::Project, Error> {
+        let url = api_v1!("/projects/@me").to_string();
+        get(self, url).await
+    }
+
+    /// Get a page of the votes for the project of the token.
+    ///
+    /// Pass the [cursor](v1::VotePage::cursor) of the previous page to get the next page.
+    /// Always uses the [v1 API](ApiVersion::V1).
+    pub async fn project_votes(&self, cursor: Option<&str>) -> Result<v1::VotePage, Error> {
+        let url = match cursor {
+            Some(cursor) => {
+                let url = api_v1!("/projects/@me/votes");
+                let url = Url::parse_with_params(url, &[("cursor", cursor)]);
+                url.map_err(Error::Url)?.to_string()
+            }
+            None => api_v1!("/projects/@me/votes").to_string(),
+        };
+        get(self, url).await
+    }
+
+    /// Get the current vote of a user for the project of the token.
+    ///
+    /// Returns `None` if the user has no active vote. Always uses the [v1 API](ApiVersion::V1).
+    pub async fn project_vote<T>(&self, user: T) -> Result<Option<v1::Vote>, Error>
+    where
+        T: Into<UserId>,
+    {
+        let url = api_v1!("/projects/@me/votes/{}?source=discord", user.into());
+        match get(self, url).await {
+            Ok(vote) => Ok(Some(vote)),
+            Err(e) if e.status() == Some(StatusCode::NOT_FOUND) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get the reviews of a bot.
+    ///
+    /// The reviews are fetched page by page while the stream is polled.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use futures_util::TryStreamExt;
+    ///
+    /// # async fn run(client: dbl::Client) -> Result<(), dbl::Error> {
+    /// let mut reviews = Box::pin(client.reviews(565_030_624_499_466_240));
+    /// while let Some(review) = reviews.try_next().await? {
+    ///     println!("{}: {}", review.rating, review.content);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reviews<T>(&self, bot: T) -> impl Stream<Item = Result<Review, Error>> + '_
+    where
+        T: Into<BotId>,
+    {
+        let bot = bot.into();
+        let pages = stream::try_unfold(Some(0), move |offset| async move {
+            let offset = match offset {
+                Some(offset) => offset,
+                None => return Ok(None),
+            };
+            let url = api!(
+                "/bots/{}/reviews?limit={}&offset={}",
+                bot,
+                REVIEWS_PAGE_SIZE,
+                offset
+            );
+            let page: ReviewPage = get(self, url).await?;
+            let next = offset + page.results.len() as u64;
+            let next = if page.results.is_empty() || next >= page.total {
+                None
+            } else {
+                Some(next)
+            };
+            Ok::<_, Error>(Some((page.results, next)))
+        });
+        pages
+            .map_ok(|reviews| stream::iter(reviews.into_iter().map(Ok)))
+            .try_flatten()
+    }
+
+    /// Get the aggregated ratings of all reviews of a bot.
+    pub async fn rating_summary<T>(&self, bot: T) -> Result<RatingSummary, Error>
+    where
+        T: Into<BotId>,
+    {
+        let reviews: Vec<Review> = self.reviews(bot).try_collect().await?;
+        Ok(RatingSummary::from_reviews(&reviews))
+    }
+
+    /// Get information about a specific server.
+    pub async fn server<T>(&self, guild: T) -> Result<Server, Error>
+    where
+        T: Into<GuildId>,
+    {
+        let url = api!("/servers/{}", guild.into());
+        get(self, url).await
+    }
+
+    /// Search for servers.
+    ///
+    /// See [`Client::search`] for an example of a [`Filter`].
+    pub async fn search_servers(&self, filter: &Filter) -> Result<ServerListing, Error> {
+        let url = Url::parse_with_params(api!("/servers"), &filter.0).map_err(Error::Url)?;
+        get(self, url.to_string()).await
+    }
+
+    /// Get the stats of a server.
+    pub async fn server_stats<T>(&self, guild: T) -> Result<ServerStats, Error>
+    where
+        T: Into<GuildId>,
+    {
+        let url = api!("/servers/{}/stats", guild.into());
+        get(self, url).await
+    }
+
+    /// Get the last 1000 votes for a server.
+    pub async fn server_votes<T>(&self, guild: T) -> Result<Vec<User>, Error>
+    where
+        T: Into<GuildId>,
+    {
+        let url = api!("/servers/{}/votes", guild.into());
+        get(self, url).await
+    }
+
+    /// Check if a user has voted for a server in the past 24 hours.
+    pub async fn server_has_voted<T, U>(&self, guild: T, user: U) -> Result<bool, Error>
+    where
+        T: Into<GuildId>,
+        U: Into<UserId>,
+    {
+        let guild = guild.into();
+        let user = user.into();
+        let url = api!("/servers/{}/check?userId={}", guild, user);
+        let v: UserVoted = get(self, url).await?;
+        Ok(v.voted > 0)
+    }
+
+    /// Check if the weekend multiplier is active.
+    ///
+    /// See [`WeekendCache`](crate::weekend::WeekendCache) for a cached variant.
+    pub async fn is_weekend(&self) -> Result<bool, Error> {
+        let url = api!("/weekend").to_string();
+        let v: IsWeekend = get(self, url).await?;
+        Ok(v.is_weekend)
+    }
+
+    /// Get information about a user.
+    pub async fn user<T>(&self, user: T) -> Result<DetailedUser, Error>
+    where
+        T: Into<UserId>,
+    {
+        let url = api!("/users/{}", user.into());
+        get(self, url).await
+    }
+
+    /// Get information about many users with at most `concurrency` requests in flight.
+    ///
+    /// A failed request only fails the result of the affected user.
+    pub async fn users_many<I>(
+        &self,
+        users: I,
+        concurrency: usize,
+    ) -> HashMap<UserId, Result<DetailedUser, Error>>
+    where
+        I: IntoIterator,
+        I::Item: Into<UserId>,
+    {
+        let users = users.into_iter().map(Into::into);
+        batch(users, concurrency, |user| self.user(user)).await
+    }
+}
+
+/// Runs the requests for `ids` concurrently and collects the results by id.
+///
+/// Requests failing because of the rate limit are retried once after the rate limit expired.
+async fn batch<I, K, V, F, Fut>(ids: I, concurrency: usize, f: F) -> HashMap<K, Result<V, Error>>
+where
+    I: IntoIterator<Item = K>,
+    K: Copy + Eq + Hash,
+    F: Fn(K) -> Fut,
+    Fut: Future<Output = Result<V, Error>>,
+{
+    let f = &f;
+    stream::iter(ids)
+        .map(|id| async move {
+            let result = match f(id).await {
+                Err(e) if e.is_ratelimit() => f(id).await,
+                result => result,
+            };
+            (id, result)
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await
+}
+
+async fn request<T>(
+    client: &Client,
+    method: Method,
+    url: String,
+    data: Option<T>,
+) -> Result<Vec<u8>, Error>
+where
+    T: serde::Serialize + Sized,
+{
+    let mut req = client.client.request(method, &url);
+    // The legacy routes only accept the raw token, even if the client uses the v1 API.
+    req = if client.version == ApiVersion::V1 && url.starts_with(api_v1!("")) {
+        req.bearer_auth(&client.token)
+    } else {
+        req.header(AUTHORIZATION, &*client.token)
+    };
+
+    if let Some(data) = data {
+        req = req.json(&data);
+    }
+
+    let mut req = req.build().map_err(error::from)?;
+    for hook in &client.hooks {
+        hook.before_send(&mut req);
+    }
+    let method = req.method().clone();
+
+    let blocked_until = *client.ratelimit.lock().unwrap();
+    if let Some(until) = blocked_until {
+        time::sleep_until(until).await;
+    }
+
+    let resp = match client.client.execute(req).await {
+        Ok(resp) => resp,
+        Err(e) => return Err(error::from(e)),
+    };
+    let status = resp.status();
+    let url = resp.url().clone();
+    let headers = resp.headers().clone();
+    let result = resp.error_for_status_ref().map(|_| ()).map_err(error::from);
+    let body = resp.bytes().await.map_err(error::from)?;
+
+    let received = hook::Response {
+        method: &method,
+        url: &url,
+        status,
+        headers: &headers,
+        body: &body,
+    };
+    for hook in &client.hooks {
+        hook.after_receive(&received);
+    }
+
+    match status {
+        StatusCode::TOO_MANY_REQUESTS => {
+            let rl = match serde_json::from_slice::<Ratelimit>(&body) {
+                Ok(rl) => rl,
+                Err(e) => return Err(Error::Json(e)),
+            };
+            let until = Instant::now() + Duration::from_secs(rl.retry_after.into());
+            let mut blocked_until = client.ratelimit.lock().unwrap();
+            if blocked_until.is_none_or(|t| t < until) {
+                *blocked_until = Some(until);
+            }
+            Err(error::ratelimit(rl.retry_after))
+        }
+        _ => result.map(|_| body.to_vec()),
+    }
+}
+
+async fn get<T>(client: &Client, url: String) -> Result<T, Error>
+where
+    T: serde::de::DeserializeOwned + Sized,
+{
+    let body = request(client, Method::GET, url, None::<()>).await?;
+    serde_json::from_slice(&body).map_err(Error::Json)
+}
+
+async fn post<T>(client: &Client, url: String, data: Option<T>) -> Result<(), Error>
+where
+    T: serde::Serialize + Sized,
+{
+    request(client, Method::POST, url, data).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::TryStreamExt;
+
+    use super::*;
+    use crate::test_util::{self, Redirect};
+
+    #[tokio::test]
+    async fn reviews_pagination() {
+        let (port, server) = test_util::serve(vec![
+            (
+                "200 OK",
+                r#"{"results":[
+                    {"id":"1","author":"1","rating":5,"content":"a","timestamp":""},
+                    {"id":"2","author":"2","rating":4,"content":"b","timestamp":""}
+                ],"total":3}"#,
+            ),
+            (
+                "200 OK",
+                r#"{"results":[
+                    {"id":"3","author":"3","rating":3,"content":"c","timestamp":"","replies":[
+                        {"id":"4","author":"4","content":"d","timestamp":""}
+                    ]}
+                ],"total":3}"#,
+            ),
+        ]);
+        let client = Client::new("token".into())
+            .unwrap()
+            .with_hook(Redirect(port));
+
+        let reviews: Vec<Review> = client.reviews(1).try_collect().await.unwrap();
+        let ids = reviews.iter().map(|r| &*r.id).collect::<Vec<_>>();
+        assert_eq!(ids, ["1", "2", "3"]);
+        assert_eq!(reviews[2].replies[0].author, UserId(4));
+
+        let requests = server.join().unwrap();
+        let request_line = "GET /api/bots/1/reviews?limit=50&offset=2 HTTP/1.1";
+        assert_eq!(requests[1].request_line, request_line);
+    }
+
+    #[tokio::test]
+    async fn users_many_partial_failure() {
+        let (port, server) = test_util::serve(vec![
+            ("429 Too Many Requests", r#"{"retry_after":0}"#),
+            (
+                "200 OK",
+                r#"{"id":"1","username":"foo","discriminator":"0","avatar":null,
+                    "defAvatar":"","bio":null,"banner":null,"social":null,"color":null,
+                    "supporter":false,"certifiedDev":false,"mod":false,"webMod":false,
+                    "admin":false}"#,
+            ),
+            ("404 Not Found", r#"{"error":"Not found"}"#),
+        ]);
+        let client = Client::new("token".into())
+            .unwrap()
+            .with_hook(Redirect(port));
+
+        let users = client.users_many(vec![1, 2], 1).await;
+        assert_eq!(users[&UserId(1)].as_ref().unwrap().username, "foo");
+        let err = users[&UserId(2)].as_ref().unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::NOT_FOUND));
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn update_stats_invalid() {
+        let client = Client::new("token".into()).unwrap();
+        let stats = ShardStats::Shard {
+            server_count: 10,
+            shard_id: 4,
+            shard_count: 4,
+        };
+
+        let err = client.update_stats(1, stats).await.unwrap_err();
+        assert!(matches!(
+            err,
+            Error::InvalidStats(ShardStatsError::InvalidShardId { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn update_stats_v1_single_shard() {
+        let client = Client::new("token".into())
+            .unwrap()
+            .with_api_version(ApiVersion::V1);
+        let stats = ShardStats::Shard {
+            server_count: 10,
+            shard_id: 0,
+            shard_count: 4,
+        };
+
+        let err = client.update_stats(1, stats).await.unwrap_err();
+        assert!(matches!(
+            err,
+            Error::InvalidStats(ShardStatsError::SingleShard)
+        ));
+    }
+
+    #[tokio::test]
+    async fn legacy_routes_v1() {
+        let (port, server) = test_util::serve(vec![("200 OK", r#"{"shards":[]}"#)]);
+        let client = Client::new("token".into())
+            .unwrap()
+            .with_api_version(ApiVersion::V1)
+            .with_hook(Redirect(port));
+
+        client.stats(1).await.unwrap();
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests[0].request_line, "GET /api/bots/1/stats HTTP/1.1");
+        assert_eq!(requests[0].header("authorization"), Some("token"));
+    }
+
+    #[tokio::test]
+    async fn has_voted_v1() {
+        let (port, server) = test_util::serve(vec![
+            (
+                "200 OK",
+                r#"{"user_id":"9","platform_id":"2","weight":1,
+                    "created_at":"2024-01-01T00:00:00Z","expires_at":"2024-01-01T12:00:00Z"}"#,
+            ),
+            ("404 Not Found", r#"{"title":"Not Found"}"#),
+        ]);
+        let client = Client::new("token".into())
+            .unwrap()
+            .with_api_version(ApiVersion::V1)
+            .with_hook(Redirect(port));
+
+        let status = client.vote_status(1, 2).await.unwrap();
+        assert!(status.voted);
+        assert_eq!(status.is_weekend, Some(false));
+        assert_eq!(status.next_vote_at.as_deref(), Some("2024-01-01T12:00:00Z"));
+        assert!(!client.has_voted(1, 3).await.unwrap());
+
+        let requests = server.join().unwrap();
+        let request_line = "GET /api/v1/projects/@me/votes/2?source=discord HTTP/1.1";
+        assert_eq!(requests[0].request_line, request_line);
+        assert_eq!(requests[0].header("authorization"), Some("bearer token"));
+    }
+}
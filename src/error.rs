@@ -3,11 +3,18 @@ use std::fmt;
 use reqwest::StatusCode;
 use url::ParseError;
 
+use crate::types::ShardStatsError;
+
 #[derive(Debug)]
 pub enum Error {
-    Ratelimit { retry_after: u32 },
+    Ratelimit {
+        retry_after: u32,
+    },
     Reqwest(reqwest::Error),
+    Json(serde_json::Error),
     Url(ParseError),
+    /// Invalid stats rejected by [`Client::update_stats`](crate::Client::update_stats).
+    InvalidStats(ShardStatsError),
 }
 
 impl Error {
@@ -19,7 +26,7 @@ impl Error {
         match self {
             Error::Ratelimit { .. } => Some(StatusCode::TOO_MANY_REQUESTS),
             Error::Reqwest(e) => e.status(),
-            Error::Url(_) => None,
+            Error::Json(_) | Error::Url(_) | Error::InvalidStats(_) => None,
         }
     }
 }
@@ -33,7 +40,9 @@ impl fmt::Display for Error {
                 write!(f, "Ratelimit reached, retry after: {}", retry_after)
             }
             Error::Reqwest(e) => e.fmt(f),
+            Error::Json(e) => e.fmt(f),
             Error::Url(e) => e.fmt(f),
+            Error::InvalidStats(e) => e.fmt(f),
         }
     }
 }
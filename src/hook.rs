@@ -0,0 +1,134 @@
+//! Interceptor hooks for requests made by the [`Client`](crate::Client).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use dbl::hook::{Hook, Response};
+//! use dbl::Client;
+//!
+//! struct Logger;
+//!
+//! impl Hook for Logger {
+//!     fn before_send(&self, req: &mut reqwest::Request) {
+//!         req.headers_mut()
+//!             .insert("x-correlation-id", "1234".parse().unwrap());
+//!         println!("--> {} {}", req.method(), req.url());
+//!     }
+//!
+//!     fn after_receive(&self, resp: &Response<'_>) {
+//!         println!("<-- {} {}", resp.status(), resp.url());
+//!     }
+//! }
+//!
+//! # fn main() -> Result<(), dbl::Error> {
+//! let client = Client::new("token".into())?.with_hook(Logger);
+//! # Ok(())
+//! # }
+//! ```
+
+use reqwest::header::HeaderMap;
+use reqwest::{Method, Request, StatusCode};
+use url::Url;
+
+/// Hook invoked around every request of the [`Client`](crate::Client).
+///
+/// Hooks are called in the order they were added with
+/// [`Client::with_hook`](crate::Client::with_hook).
+pub trait Hook: Send + Sync {
+    /// Called before the request is sent.
+    ///
+    /// The request can be modified, e.g. to add headers.
+    fn before_send(&self, req: &mut Request) {
+        let _ = req;
+    }
+
+    /// Called after the response has been received, before it is processed by the client.
+    fn after_receive(&self, resp: &Response<'_>) {
+        let _ = resp;
+    }
+}
+
+/// Response passed to [`Hook::after_receive`].
+pub struct Response<'a> {
+    pub(crate) method: &'a Method,
+    pub(crate) url: &'a Url,
+    pub(crate) status: StatusCode,
+    pub(crate) headers: &'a HeaderMap,
+    pub(crate) body: &'a [u8],
+}
+
+impl Response<'_> {
+    /// Method of the request.
+    pub fn method(&self) -> &Method {
+        self.method
+    }
+
+    /// Final URL of the request.
+    pub fn url(&self) -> &Url {
+        self.url
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn headers(&self) -> &HeaderMap {
+        self.headers
+    }
+
+    /// Raw response body.
+    pub fn body(&self) -> &[u8] {
+        self.body
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::test_util::{self, Redirect};
+    use crate::Client;
+
+    struct CorrelationId;
+
+    impl Hook for CorrelationId {
+        fn before_send(&self, req: &mut Request) {
+            req.headers_mut()
+                .insert("x-correlation-id", "42".parse().unwrap());
+        }
+    }
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<(StatusCode, Vec<u8>)>>);
+
+    impl Hook for Arc<Recorder> {
+        fn after_receive(&self, resp: &Response<'_>) {
+            let mut calls = self.0.lock().unwrap();
+            calls.push((resp.status(), resp.body().to_vec()));
+        }
+    }
+
+    #[tokio::test]
+    async fn hooks_are_called() {
+        let (port, server) = test_util::serve(vec![("200 OK", r#"{"voted":1}"#)]);
+
+        let recorder = Arc::new(Recorder::default());
+        let client = Client::new("token".into())
+            .unwrap()
+            .with_hook(CorrelationId)
+            .with_hook(Redirect(port))
+            .with_hook(recorder.clone());
+
+        assert!(client.has_voted(1, 2).await.unwrap());
+
+        let requests = server.join().unwrap();
+        let request_line = "GET /api/bots/1/check?userId=2 HTTP/1.1";
+        assert_eq!(requests[0].request_line, request_line);
+        assert_eq!(requests[0].header("x-correlation-id"), Some("42"));
+        assert_eq!(requests[0].header("authorization"), Some("token"));
+
+        let calls = recorder.0.lock().unwrap();
+        assert_eq!(*calls, vec![(StatusCode::OK, br#"{"voted":1}"#.to_vec())]);
+    }
+}
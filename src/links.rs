@@ -0,0 +1,327 @@
+//! URL Builders for top.gg pages and Discord bot invites.
+//!
+//! # Example
+//!
+//! ```
+//! use dbl::links::{Invite, Permissions};
+//!
+//! let url = Invite::new("565030624499466240")
+//!     .permissions(Permissions::SEND_MESSAGES | Permissions::EMBED_LINKS)
+//!     .build();
+//!
+//! assert_eq!(
+//!     url.as_str(),
+//!     "https://discord.com/oauth2/authorize?client_id=565030624499466240&permissions=18432&scope=bot+applications.commands"
+//! );
+//! ```
+
+use std::fmt;
+use std::ops::{BitAnd, BitOr, BitOrAssign};
+
+use url::{ParseError, Url};
+
+use crate::types::{Bot, BotId};
+
+const AUTHORIZE_URL: &str = "https://discord.com/oauth2/authorize";
+
+macro_rules! site {
+    ($e:expr, $($rest:tt)*) => {
+        format!(concat!("https://top.gg", $e), $($rest)*)
+    };
+}
+
+/// URL Builder for vote links with query parameters.
+///
+/// The query parameters are passed to the webhook and can be read with
+/// [`Webhook::query_pairs`](crate::types::Webhook::query_pairs) or
+/// `Webhook::query_as` with the `query` feature.
+///
+/// # Example
+///
+/// ```
+/// use dbl::links::VoteLink;
+///
+/// let url = VoteLink::new()
+///     .param("campaign", "spring")
+///     .param("ref", "reddit")
+///     .build(565_030_624_499_466_240)
+///     .unwrap();
+///
+/// assert_eq!(
+///     url.as_str(),
+///     "https://top.gg/bot/565030624499466240/vote?campaign=spring&ref=reddit"
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct VoteLink(Vec<(String, String)>);
+
+impl VoteLink {
+    pub fn new() -> Self {
+        VoteLink(Vec::new())
+    }
+
+    /// Adds a query parameter.
+    pub fn param<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: ToString,
+    {
+        self.0.push((key.into(), value.to_string()));
+        self
+    }
+
+    /// Build the vote link.
+    pub fn build<T>(self, bot: T) -> Result<Url, ParseError>
+    where
+        T: Into<BotId>,
+    {
+        let url = site!("/bot/{}/vote", bot.into());
+        if self.0.is_empty() {
+            Url::parse(&url)
+        } else {
+            Url::parse_with_params(&url, self.0)
+        }
+    }
+}
+
+impl Bot {
+    /// Returns the URL of the bot's profile page, using the vanity URL if the bot has one.
+    pub fn profile_url(&self) -> Url {
+        parse(site!("/bot/{}", self.slug()))
+    }
+
+    /// Returns the URL of the bot's vote page.
+    pub fn vote_url(&self) -> Url {
+        parse(site!("/bot/{}/vote", self.slug()))
+    }
+
+    /// Returns the URL of the bot's reviews.
+    pub fn reviews_url(&self) -> Url {
+        parse(site!("/bot/{}#reviews", self.slug()))
+    }
+
+    /// Returns the URL of the bot's edit page.
+    pub fn edit_url(&self) -> Url {
+        parse(site!("/bot/{}/edit", self.id))
+    }
+
+    /// Returns an [`Invite`] builder for the bot's client id.
+    pub fn invite_builder(&self) -> Invite {
+        Invite::new(&*self.clientid)
+    }
+
+    /// Parses the bot's custom [`invite`](Bot::invite) URL.
+    ///
+    /// Returns `None` if the bot has no invite URL or it isn't a Discord OAuth2 URL.
+    pub fn parsed_invite(&self) -> Option<Invite> {
+        self.invite.as_deref().and_then(Invite::parse)
+    }
+
+    fn slug(&self) -> String {
+        match self.vanity.as_deref() {
+            Some(vanity) if !vanity.is_empty() => vanity.to_string(),
+            _ => self.id.to_string(),
+        }
+    }
+}
+
+fn parse(url: String) -> Url {
+    Url::parse(&url).expect("valid top.gg url")
+}
+
+/// Bitset of Discord permissions for bot invites.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Permissions(pub u64);
+
+impl Permissions {
+    pub const CREATE_INSTANT_INVITE: Permissions = Permissions(1 << 0);
+    pub const KICK_MEMBERS: Permissions = Permissions(1 << 1);
+    pub const BAN_MEMBERS: Permissions = Permissions(1 << 2);
+    pub const ADMINISTRATOR: Permissions = Permissions(1 << 3);
+    pub const MANAGE_CHANNELS: Permissions = Permissions(1 << 4);
+    pub const MANAGE_GUILD: Permissions = Permissions(1 << 5);
+    pub const ADD_REACTIONS: Permissions = Permissions(1 << 6);
+    pub const VIEW_AUDIT_LOG: Permissions = Permissions(1 << 7);
+    pub const VIEW_CHANNEL: Permissions = Permissions(1 << 10);
+    pub const SEND_MESSAGES: Permissions = Permissions(1 << 11);
+    pub const MANAGE_MESSAGES: Permissions = Permissions(1 << 13);
+    pub const EMBED_LINKS: Permissions = Permissions(1 << 14);
+    pub const ATTACH_FILES: Permissions = Permissions(1 << 15);
+    pub const READ_MESSAGE_HISTORY: Permissions = Permissions(1 << 16);
+    pub const MENTION_EVERYONE: Permissions = Permissions(1 << 17);
+    pub const USE_EXTERNAL_EMOJIS: Permissions = Permissions(1 << 18);
+    pub const CONNECT: Permissions = Permissions(1 << 20);
+    pub const SPEAK: Permissions = Permissions(1 << 21);
+    pub const MANAGE_NICKNAMES: Permissions = Permissions(1 << 27);
+    pub const MANAGE_ROLES: Permissions = Permissions(1 << 28);
+    pub const MANAGE_WEBHOOKS: Permissions = Permissions(1 << 29);
+    pub const USE_APPLICATION_COMMANDS: Permissions = Permissions(1 << 31);
+
+    pub const fn empty() -> Self {
+        Permissions(0)
+    }
+
+    pub const fn bits(self) -> u64 {
+        self.0
+    }
+
+    /// Returns `true` if all permissions of `other` are set.
+    pub const fn contains(self, other: Permissions) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for Permissions {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Permissions(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Permissions {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitAnd for Permissions {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Permissions(self.0 & rhs.0)
+    }
+}
+
+/// OAuth2 scope of a bot invite.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Scope {
+    Bot,
+    ApplicationsCommands,
+    Other(String),
+}
+
+impl Scope {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Scope::Bot => "bot",
+            Scope::ApplicationsCommands => "applications.commands",
+            Scope::Other(scope) => scope,
+        }
+    }
+}
+
+impl From<&str> for Scope {
+    fn from(scope: &str) -> Self {
+        match scope {
+            "bot" => Scope::Bot,
+            "applications.commands" => Scope::ApplicationsCommands,
+            scope => Scope::Other(scope.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// URL Builder for Discord OAuth2 bot invites.
+///
+/// Defaults to the `bot` and `applications.commands` scopes without permissions.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Invite {
+    pub client_id: String,
+    pub permissions: Permissions,
+    pub scopes: Vec<Scope>,
+}
+
+impl Invite {
+    pub fn new<T: Into<String>>(client_id: T) -> Self {
+        Invite {
+            client_id: client_id.into(),
+            permissions: Permissions::empty(),
+            scopes: vec![Scope::Bot, Scope::ApplicationsCommands],
+        }
+    }
+
+    pub fn permissions(mut self, permissions: Permissions) -> Self {
+        self.permissions = permissions;
+        self
+    }
+
+    /// Replaces the scopes of the invite.
+    pub fn scopes<I>(mut self, scopes: I) -> Self
+    where
+        I: IntoIterator<Item = Scope>,
+    {
+        self.scopes = scopes.into_iter().collect();
+        self
+    }
+
+    /// Build the invite url.
+    pub fn build(&self) -> Url {
+        let scopes = self
+            .scopes
+            .iter()
+            .map(Scope::as_str)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let params = [
+            ("client_id", self.client_id.clone()),
+            ("permissions", self.permissions.bits().to_string()),
+            ("scope", scopes),
+        ];
+        Url::parse_with_params(AUTHORIZE_URL, &params).expect("valid invite url")
+    }
+
+    /// Parses a Discord OAuth2 invite URL.
+    pub fn parse(url: &str) -> Option<Invite> {
+        let url = Url::parse(url).ok()?;
+        let host = url.host_str()?;
+        let discord = ["discord.com", "discordapp.com"]
+            .iter()
+            .any(|d| host == *d || host.ends_with(&format!(".{}", d)));
+        if !discord || !url.path().ends_with("/oauth2/authorize") {
+            return None;
+        }
+
+        let mut invite = Invite::new(String::new()).scopes(vec![]);
+        for (key, value) in url.query_pairs() {
+            match &*key {
+                "client_id" => invite.client_id = value.into_owned(),
+                "permissions" => invite.permissions = Permissions(value.parse().ok()?),
+                "scope" => invite.scopes = value.split(' ').map(Scope::from).collect(),
+                _ => {}
+            }
+        }
+        if invite.client_id.is_empty() {
+            return None;
+        }
+        Some(invite)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_invite() {
+        let url = "https://discordapp.com/api/oauth2/authorize?client_id=1&scope=bot";
+        let invite = Invite::parse(url).unwrap();
+        assert_eq!(invite.permissions, Permissions::empty());
+        assert_eq!(invite.scopes, [Scope::Bot]);
+
+        let url =
+            "https://discord.com/oauth2/authorize?client_id=1&scope=bot%20identify&permissions=8";
+        let invite = Invite::parse(url).unwrap();
+        assert_eq!(invite.client_id, "1");
+        assert!(invite.permissions.contains(Permissions::ADMINISTRATOR));
+        assert_eq!(invite.scopes, [Scope::Bot, Scope::Other("identify".into())]);
+
+        assert_eq!(Invite::parse(invite.build().as_str()), Some(invite));
+        assert_eq!(Invite::parse("https://top.gg/bot/1/invite"), None);
+    }
+}
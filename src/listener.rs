@@ -0,0 +1,303 @@
+//! Vote events combined from webhook deliveries and polling of the votes endpoint.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use dbl::listener::VoteListener;
+//! use dbl::Client;
+//!
+//! # async fn run() -> Result<(), dbl::Error> {
+//! let client = Client::new("token".into())?;
+//! let mut listener = VoteListener::new(client, 565_030_624_499_466_240);
+//!
+//! // Pass the sender to the webhook receiver.
+//! let sender = listener.sender();
+//!
+//! loop {
+//!     let vote = listener.next().await?;
+//!     println!("{} voted", vote.user);
+//! }
+//! # }
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tokio::time::{self, Instant, Interval, MissedTickBehavior};
+
+use crate::types::{BotId, UserId, Webhook};
+use crate::{Client, Error};
+
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const DEFAULT_DEDUP_WINDOW: Duration = Duration::from_secs(12 * 60 * 60);
+
+/// New vote yielded by the [`VoteListener`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Vote {
+    pub user: UserId,
+    pub source: Source,
+}
+
+/// Source of a [`Vote`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Source {
+    /// The vote was delivered by a webhook.
+    Webhook(Webhook),
+    /// The vote was found by polling [`Client::votes`].
+    Poll,
+}
+
+/// Sender half used to pass webhook deliveries to a [`VoteListener`].
+pub type WebhookSender = mpsc::UnboundedSender<Webhook>;
+
+/// Listener that yields new voters of a bot.
+///
+/// Webhook deliveries are passed to the listener with the [`WebhookSender`] returned by
+/// [`VoteListener::sender`]. In addition the listener polls [`Client::votes`] periodically
+/// and diffs the result with the previous poll to backfill missed webhooks.
+///
+/// Votes of the same user reported by both sources within the dedup window are only yielded
+/// once. Test votes of the webhook are dropped unless enabled with
+/// [`include_test`](VoteListener::include_test).
+pub struct VoteListener {
+    client: Client,
+    bot: BotId,
+    tx: WebhookSender,
+    rx: mpsc::UnboundedReceiver<Webhook>,
+    poll_interval: Duration,
+    interval: Option<Interval>,
+    previous: Option<Vec<UserId>>,
+    seen: Seen,
+    pending: VecDeque<Vote>,
+    include_test: bool,
+}
+
+impl VoteListener {
+    /// Constructs a new `VoteListener` for the given bot.
+    pub fn new<T>(client: Client, bot: T) -> Self
+    where
+        T: Into<BotId>,
+    {
+        let (tx, rx) = mpsc::unbounded_channel();
+        VoteListener {
+            client,
+            bot: bot.into(),
+            tx,
+            rx,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            interval: None,
+            previous: None,
+            seen: Seen::new(DEFAULT_DEDUP_WINDOW),
+            pending: VecDeque::new(),
+            include_test: false,
+        }
+    }
+
+    /// Sets the interval for polling the votes endpoint. Defaults to 5 minutes.
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Sets the window in which votes of the same user are considered duplicates.
+    /// Defaults to 12 hours.
+    pub fn dedup_window(mut self, window: Duration) -> Self {
+        self.seen.window = window;
+        self
+    }
+
+    /// Yield test votes of the webhook. Defaults to `false`.
+    ///
+    /// Test votes aren't deduplicated.
+    pub fn include_test(mut self, include: bool) -> Self {
+        self.include_test = include;
+        self
+    }
+
+    /// Returns a sender for passing webhook deliveries to the listener.
+    pub fn sender(&self) -> WebhookSender {
+        self.tx.clone()
+    }
+
+    /// Waits for the next new vote.
+    ///
+    /// The first poll only records the current voters. Errors of the votes endpoint are
+    /// returned, the listener can be used again afterwards.
+    pub async fn next(&mut self) -> Result<Vote, Error> {
+        loop {
+            if let Some(vote) = self.pending.pop_front() {
+                return Ok(vote);
+            }
+
+            let poll_interval = self.poll_interval;
+            let interval = self.interval.get_or_insert_with(|| {
+                let mut interval = time::interval(poll_interval);
+                interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                interval
+            });
+
+            tokio::select! {
+                Some(hook) = self.rx.recv() => {
+                    if hook.bot != self.bot || (hook.is_test() && !self.include_test) {
+                        continue;
+                    }
+                    if hook.is_test() || self.seen.insert(hook.user, Instant::now()) {
+                        let user = hook.user;
+                        return Ok(Vote { user, source: Source::Webhook(hook) });
+                    }
+                }
+                _ = interval.tick() => {
+                    self.poll().await?;
+                }
+            }
+        }
+    }
+
+    async fn poll(&mut self) -> Result<(), Error> {
+        let votes = self.client.votes(self.bot).await?;
+        let current = votes.into_iter().map(|u| u.id).collect::<Vec<_>>();
+
+        if let Some(previous) = self.previous.take() {
+            let now = Instant::now();
+            for &user in new_votes(&previous, &current).iter().rev() {
+                if self.seen.insert(user, now) {
+                    let source = Source::Poll;
+                    self.pending.push_back(Vote { user, source });
+                }
+            }
+        }
+        self.previous = Some(current);
+        Ok(())
+    }
+}
+
+/// Returns the votes of `current` that aren't in `previous`.
+///
+/// Both lists are ordered from the newest to the oldest vote and capped at the same length,
+/// so `current` consists of the new votes followed by the newest votes of `previous`. Diffing
+/// the order instead of the set of voters also finds new votes of users already in the list.
+fn new_votes<'a>(previous: &[UserId], current: &'a [UserId]) -> &'a [UserId] {
+    let new = (0..current.len())
+        .find(|&n| previous.starts_with(&current[n..]))
+        .unwrap_or(current.len());
+    &current[..new]
+}
+
+/// Users with a vote inside the dedup window.
+struct Seen {
+    window: Duration,
+    users: HashMap<UserId, Instant>,
+}
+
+impl Seen {
+    fn new(window: Duration) -> Self {
+        Seen {
+            window,
+            users: HashMap::new(),
+        }
+    }
+
+    /// Records a vote of `user` and returns `false` if the user has already voted inside the
+    /// dedup window.
+    fn insert(&mut self, user: UserId, now: Instant) -> bool {
+        let window = self.window;
+        self.users.retain(|_, t| now.duration_since(*t) < window);
+        if self.users.contains_key(&user) {
+            return false;
+        }
+        self.users.insert(user, now);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, Redirect};
+    use crate::types::WebhookType;
+
+    #[test]
+    fn new_votes_of_repeat_voters() {
+        let ids = |ids: &[u64]| ids.iter().map(|&id| UserId(id)).collect::<Vec<_>>();
+
+        let previous = ids(&[2, 1]);
+        assert_eq!(new_votes(&previous, &ids(&[2, 1])), []);
+        assert_eq!(new_votes(&previous, &ids(&[1, 3, 2, 1])), ids(&[1, 3]));
+        // Capped lists drop the oldest votes.
+        assert_eq!(new_votes(&previous, &ids(&[1, 2])), ids(&[1]));
+        assert_eq!(new_votes(&previous, &ids(&[3, 4])), ids(&[3, 4]));
+    }
+
+    #[tokio::test]
+    async fn next_merges_sources() {
+        let (port, server) = test_util::serve(vec![
+            ("200 OK", r#"[{"id":"2"}]"#),
+            ("200 OK", r#"[{"id":"2"},{"id":"3"},{"id":"2"}]"#),
+        ]);
+        let client = Client::new("token".into())
+            .unwrap()
+            .with_hook(Redirect(port));
+        let mut listener = VoteListener::new(client, 1).poll_interval(Duration::from_millis(100));
+
+        // Suppresses the vote of the second poll.
+        let sender = listener.sender();
+        sender.send(Webhook::new(1, 6, WebhookType::Test)).unwrap();
+        sender
+            .send(Webhook::new(1, 3, WebhookType::Upvote))
+            .unwrap();
+        sender
+            .send(Webhook::new(4, 5, WebhookType::Upvote))
+            .unwrap();
+
+        let vote = listener.next().await.unwrap();
+        assert_eq!(vote.user, UserId(3));
+        assert!(matches!(vote.source, Source::Webhook(_)));
+
+        // The first poll is the baseline, the second poll finds the repeated vote of user 2.
+        let vote = listener.next().await.unwrap();
+        assert_eq!(
+            vote,
+            Vote {
+                user: UserId(2),
+                source: Source::Poll,
+            }
+        );
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].request_line, "GET /api/bots/1/votes HTTP/1.1");
+    }
+
+    #[tokio::test]
+    async fn include_test_votes() {
+        let (port, _server) = test_util::serve(vec![("200 OK", "[]")]);
+        let client = Client::new("token".into())
+            .unwrap()
+            .with_hook(Redirect(port));
+        let mut listener = VoteListener::new(client, 1).include_test(true);
+
+        let hook = Webhook::new(1, 6, WebhookType::Test);
+        listener.sender().send(hook.clone()).unwrap();
+
+        let vote = listener.next().await.unwrap();
+        assert_eq!(
+            vote,
+            Vote {
+                user: UserId(6),
+                source: Source::Webhook(hook),
+            }
+        );
+    }
+
+    #[test]
+    fn seen_suppresses_duplicates() {
+        let mut seen = Seen::new(Duration::from_secs(60));
+        let now = Instant::now();
+
+        assert!(seen.insert(UserId(1), now));
+        assert!(seen.insert(UserId(2), now));
+        assert!(!seen.insert(UserId(1), now + Duration::from_secs(30)));
+        assert!(seen.insert(UserId(1), now + Duration::from_secs(60)));
+    }
+}
@@ -0,0 +1,314 @@
+//! Reminders for users who can vote again.
+//!
+//! Users can vote for a bot every 12 hours. The [`VoteReminder`] records the vote time of each
+//! user from the received webhooks and calls a callback when the user can vote again.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use dbl::reminder::{FileStore, VoteReminder};
+//!
+//! # async fn run(hook: dbl::types::Webhook) -> std::io::Result<()> {
+//! let store = FileStore::open("reminders.json")?;
+//! let reminder = VoteReminder::new(store);
+//!
+//! // Record the votes in the webhook handler.
+//! let handle = reminder.clone();
+//! handle.record(&hook)?;
+//!
+//! reminder
+//!     .run(|user| println!("{} can vote again", user))
+//!     .await
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tokio::sync::Notify;
+
+use crate::types::{UserId, Webhook};
+
+/// Time after which a user can vote again.
+pub const VOTE_COOLDOWN: Duration = Duration::from_secs(12 * 60 * 60);
+
+/// Storage for the vote times of the users.
+pub trait ReminderStore: Send + Sync {
+    /// Returns all recorded votes.
+    fn load(&self) -> io::Result<Vec<(UserId, SystemTime)>>;
+
+    /// Records the vote time of a user, replacing a previous one.
+    fn insert(&self, user: UserId, voted_at: SystemTime) -> io::Result<()>;
+
+    /// Removes the vote time of a user if it's still `voted_at`.
+    ///
+    /// Returns `false` if the user voted again in the meantime and the entry was kept.
+    fn remove(&self, user: UserId, voted_at: SystemTime) -> io::Result<bool>;
+}
+
+/// In-memory [`ReminderStore`]. Recorded votes are lost on restart.
+#[derive(Debug, Default)]
+pub struct MemoryStore(Mutex<HashMap<UserId, SystemTime>>);
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ReminderStore for MemoryStore {
+    fn load(&self) -> io::Result<Vec<(UserId, SystemTime)>> {
+        let entries = self.0.lock().unwrap();
+        Ok(entries.iter().map(|(u, t)| (*u, *t)).collect())
+    }
+
+    fn insert(&self, user: UserId, voted_at: SystemTime) -> io::Result<()> {
+        self.0.lock().unwrap().insert(user, voted_at);
+        Ok(())
+    }
+
+    fn remove(&self, user: UserId, voted_at: SystemTime) -> io::Result<bool> {
+        let mut entries = self.0.lock().unwrap();
+        if entries.get(&user) != Some(&voted_at) {
+            return Ok(false);
+        }
+        entries.remove(&user);
+        Ok(true)
+    }
+}
+
+/// [`ReminderStore`] persisted as JSON file.
+///
+/// The file is rewritten on every change. The write is blocking I/O and also happens inside
+/// the async scheduler of [`VoteReminder::run`] and in [`VoteReminder::record`], which
+/// stalls the runtime thread for the duration of the write. Implement [`ReminderStore`] on
+/// top of a database for a large number of users.
+#[derive(Debug)]
+pub struct FileStore {
+    path: PathBuf,
+    entries: Mutex<HashMap<UserId, u64>>,
+}
+
+impl FileStore {
+    /// Opens the store at `path`. The file is created on the first change if it doesn't exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let entries = match fs::read(&path) {
+            Ok(data) => serde_json::from_slice(&data)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(FileStore {
+            path,
+            entries: Mutex::new(entries),
+        })
+    }
+
+    fn save(&self, entries: &HashMap<UserId, u64>) -> io::Result<()> {
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec(entries)?)?;
+        fs::rename(tmp, &self.path)
+    }
+}
+
+impl ReminderStore for FileStore {
+    fn load(&self) -> io::Result<Vec<(UserId, SystemTime)>> {
+        let entries = self.entries.lock().unwrap();
+        let entries = entries.iter();
+        Ok(entries
+            .map(|(u, t)| (*u, UNIX_EPOCH + Duration::from_secs(*t)))
+            .collect())
+    }
+
+    fn insert(&self, user: UserId, voted_at: SystemTime) -> io::Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(user, secs(voted_at));
+        self.save(&entries)
+    }
+
+    fn remove(&self, user: UserId, voted_at: SystemTime) -> io::Result<bool> {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.get(&user) != Some(&secs(voted_at)) {
+            return Ok(false);
+        }
+        entries.remove(&user);
+        self.save(&entries)?;
+        Ok(true)
+    }
+}
+
+fn secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Scheduler that fires a callback when a user can vote again.
+///
+/// Cloned instances share the same store and scheduler.
+pub struct VoteReminder<S> {
+    inner: Arc<Inner<S>>,
+}
+
+struct Inner<S> {
+    store: S,
+    cooldown: Duration,
+    notify: Notify,
+}
+
+impl<S> Clone for VoteReminder<S> {
+    fn clone(&self) -> Self {
+        VoteReminder {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<S: ReminderStore> VoteReminder<S> {
+    /// Constructs a new `VoteReminder` with the [default cooldown](VOTE_COOLDOWN).
+    pub fn new(store: S) -> Self {
+        Self::with_cooldown(store, VOTE_COOLDOWN)
+    }
+
+    /// Constructs a new `VoteReminder` with a custom cooldown.
+    pub fn with_cooldown(store: S, cooldown: Duration) -> Self {
+        VoteReminder {
+            inner: Arc::new(Inner {
+                store,
+                cooldown,
+                notify: Notify::new(),
+            }),
+        }
+    }
+
+    /// Records the vote of a received webhook. Test votes are ignored.
+    pub fn record(&self, hook: &Webhook) -> io::Result<()> {
+        if hook.is_test() {
+            return Ok(());
+        }
+        self.record_at(hook.user, SystemTime::now())
+    }
+
+    /// Records a vote of `user` at the given time.
+    pub fn record_at(&self, user: UserId, voted_at: SystemTime) -> io::Result<()> {
+        self.inner.store.insert(user, voted_at)?;
+        self.inner.notify.notify_one();
+        Ok(())
+    }
+
+    /// Returns the time when the user can vote again, if a vote is recorded.
+    pub fn next_vote(&self, user: UserId) -> io::Result<Option<SystemTime>> {
+        let entries = self.inner.store.load()?;
+        let voted_at = entries.into_iter().find(|(u, _)| *u == user);
+        Ok(voted_at.map(|(_, t)| t + self.inner.cooldown))
+    }
+
+    /// Runs the scheduler and calls `callback` for every user who can vote again.
+    ///
+    /// Only returns if the store fails.
+    pub async fn run<F>(&self, callback: F) -> io::Result<()>
+    where
+        F: FnMut(UserId),
+    {
+        self.run_with_clock(callback, SystemTime::now).await
+    }
+
+    async fn run_with_clock<F, C>(&self, mut callback: F, clock: C) -> io::Result<()>
+    where
+        F: FnMut(UserId),
+        C: Fn() -> SystemTime,
+    {
+        loop {
+            let now = clock();
+            let mut next = None;
+            for (user, voted_at) in self.inner.store.load()? {
+                let due = voted_at + self.inner.cooldown;
+                match due.duration_since(now) {
+                    Ok(wait) if !wait.is_zero() => {
+                        next = Some(next.map_or(wait, |n: Duration| n.min(wait)));
+                    }
+                    // A vote recorded since the load replaces the entry and isn't removed.
+                    _ => {
+                        if self.inner.store.remove(user, voted_at)? {
+                            callback(user);
+                        }
+                    }
+                }
+            }
+
+            let notified = self.inner.notify.notified();
+            match next {
+                Some(wait) => {
+                    tokio::select! {
+                        _ = tokio::time::sleep(wait) => {}
+                        _ = notified => {}
+                    }
+                }
+                None => notified.await,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_store_roundtrip() {
+        let path = std::env::temp_dir().join(format!("dbl-reminder-{}.json", std::process::id()));
+        let voted_at = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+
+        let store = FileStore::open(&path).unwrap();
+        store.insert(UserId(1), voted_at).unwrap();
+        store.insert(UserId(2), voted_at).unwrap();
+        assert!(store.remove(UserId(2), voted_at).unwrap());
+        assert!(!store
+            .remove(UserId(1), voted_at + Duration::from_secs(60))
+            .unwrap());
+
+        let store = FileStore::open(&path).unwrap();
+        assert_eq!(store.load().unwrap(), vec![(UserId(1), voted_at)]);
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn memory_store_remove_matching() {
+        let store = MemoryStore::new();
+        let voted_at = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        let revoted_at = voted_at + VOTE_COOLDOWN;
+
+        store.insert(UserId(1), voted_at).unwrap();
+        store.insert(UserId(1), revoted_at).unwrap();
+        assert!(!store.remove(UserId(1), voted_at).unwrap());
+        assert_eq!(store.load().unwrap(), vec![(UserId(1), revoted_at)]);
+        assert!(store.remove(UserId(1), revoted_at).unwrap());
+        assert_eq!(store.load().unwrap(), vec![]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reminder_fires_when_due() {
+        let start = tokio::time::Instant::now();
+        let base = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        let clock = move || base + start.elapsed();
+
+        let reminder = VoteReminder::new(MemoryStore::new());
+        reminder.record_at(UserId(1), base).unwrap();
+
+        let mut users = vec![];
+        let run = reminder.run_with_clock(|user| users.push(user), clock);
+        let hours = |h: u64| Duration::from_secs(h * 60 * 60);
+        let _ = tokio::time::timeout(hours(11), run).await;
+        assert_eq!(users, vec![]);
+
+        let run = reminder.run_with_clock(|user| users.push(user), clock);
+        let _ = tokio::time::timeout(hours(2), run).await;
+        assert_eq!(users, vec![UserId(1)]);
+        assert_eq!(reminder.next_vote(UserId(1)).unwrap(), None);
+    }
+}
@@ -0,0 +1,260 @@
+//! Reward points for votes received via webhook.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use dbl::rewards::{MemoryStore, Rewards};
+//!
+//! # fn run(hook: dbl::types::Webhook) -> std::io::Result<()> {
+//! let rewards = Rewards::new(MemoryStore::new())
+//!     .points(10)
+//!     .weekend_multiplier(2)
+//!     .streak_bonus(5)
+//!     .max_streak(7);
+//!
+//! if let Some(reward) = rewards.process(&hook)? {
+//!     println!("{} got {} points", reward.user, reward.points);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::types::{UserId, Webhook};
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Accumulated rewards of a user.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Balance {
+    pub points: u64,
+    /// Number of consecutive days with a vote.
+    pub streak: u32,
+    pub last_vote: Option<SystemTime>,
+}
+
+/// Storage for the [`Balance`] of the users.
+pub trait RewardStore: Send + Sync {
+    /// Returns the balance of a user, or the default balance if the user has none.
+    fn get(&self, user: UserId) -> io::Result<Balance>;
+
+    /// Atomically updates the balance of a user and returns the new balance.
+    ///
+    /// Concurrent updates of the same user must not overwrite each other.
+    fn update(&self, user: UserId, f: &mut dyn FnMut(&mut Balance)) -> io::Result<Balance>;
+}
+
+/// In-memory [`RewardStore`].
+#[derive(Debug, Default)]
+pub struct MemoryStore(Mutex<HashMap<UserId, Balance>>);
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl RewardStore for MemoryStore {
+    fn get(&self, user: UserId) -> io::Result<Balance> {
+        let balances = self.0.lock().unwrap();
+        Ok(balances.get(&user).cloned().unwrap_or_default())
+    }
+
+    fn update(&self, user: UserId, f: &mut dyn FnMut(&mut Balance)) -> io::Result<Balance> {
+        let mut balances = self.0.lock().unwrap();
+        let balance = balances.entry(user).or_default();
+        f(balance);
+        Ok(balance.clone())
+    }
+}
+
+/// Reward granted for a vote.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Reward {
+    pub user: UserId,
+    /// Points granted for this vote.
+    pub points: u64,
+    /// Balance after the vote.
+    pub balance: Balance,
+}
+
+/// Reward engine that maps webhook votes to points.
+///
+/// The points of a vote are calculated as
+/// `points * weekend_multiplier + streak_bonus * min(streak - 1, max_streak)`,
+/// where the weekend multiplier only applies to votes on weekends.
+pub struct Rewards<S> {
+    store: S,
+    points: u64,
+    weekend_multiplier: u64,
+    streak_bonus: u64,
+    max_streak: u32,
+    include_test: bool,
+}
+
+impl<S: RewardStore> Rewards<S> {
+    /// Constructs a new `Rewards` engine that grants one point per vote.
+    pub fn new(store: S) -> Self {
+        Rewards {
+            store,
+            points: 1,
+            weekend_multiplier: 2,
+            streak_bonus: 0,
+            max_streak: 0,
+            include_test: false,
+        }
+    }
+
+    /// Points granted for a vote. Defaults to `1`.
+    pub fn points(mut self, points: u64) -> Self {
+        self.points = points;
+        self
+    }
+
+    /// Multiplier for votes on weekends. Defaults to `2`.
+    pub fn weekend_multiplier(mut self, multiplier: u64) -> Self {
+        self.weekend_multiplier = multiplier;
+        self
+    }
+
+    /// Bonus points per consecutive day with a vote. Defaults to `0`.
+    pub fn streak_bonus(mut self, bonus: u64) -> Self {
+        self.streak_bonus = bonus;
+        self
+    }
+
+    /// Maximum number of days counted for the streak bonus. Defaults to `0`.
+    pub fn max_streak(mut self, days: u32) -> Self {
+        self.max_streak = days;
+        self
+    }
+
+    /// Grant rewards for test votes. Defaults to `false`.
+    pub fn include_test(mut self, include: bool) -> Self {
+        self.include_test = include;
+        self
+    }
+
+    /// Returns a reference to the underlying store.
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Processes a vote and updates the balance of the user.
+    ///
+    /// Returns `None` for excluded test votes.
+    pub fn process(&self, hook: &Webhook) -> io::Result<Option<Reward>> {
+        self.process_at(hook, SystemTime::now())
+    }
+
+    /// Processes a vote received at the given time.
+    pub fn process_at(&self, hook: &Webhook, now: SystemTime) -> io::Result<Option<Reward>> {
+        if hook.is_test() && !self.include_test {
+            return Ok(None);
+        }
+
+        let today = day(now);
+        let mut points = 0;
+        let balance = self.store.update(hook.user, &mut |balance| {
+            balance.streak = match balance.last_vote.map(day) {
+                Some(last) if last == today => balance.streak.max(1),
+                Some(last) if last + 1 == today => balance.streak + 1,
+                _ => 1,
+            };
+
+            points = self.points;
+            if hook.is_weekend {
+                points *= self.weekend_multiplier;
+            }
+            let streak = (balance.streak - 1).min(self.max_streak);
+            points += self.streak_bonus * u64::from(streak);
+
+            balance.points += points;
+            balance.last_vote = Some(now);
+        })?;
+
+        Ok(Some(Reward {
+            user: hook.user,
+            points,
+            balance,
+        }))
+    }
+}
+
+fn day(time: SystemTime) -> u64 {
+    let secs = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    secs.as_secs() / SECS_PER_DAY
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::types::WebhookType;
+
+    fn hook(kind: WebhookType, is_weekend: bool) -> Webhook {
+        let mut hook = Webhook::new(1, 2, kind);
+        hook.is_weekend = is_weekend;
+        hook
+    }
+
+    #[test]
+    fn weekend_and_streak() {
+        let rewards = Rewards::new(MemoryStore::new())
+            .points(10)
+            .weekend_multiplier(2)
+            .streak_bonus(5)
+            .max_streak(1);
+        let start = UNIX_EPOCH + Duration::from_secs(100 * SECS_PER_DAY);
+        let vote = |offset: u64, weekend| {
+            let now = start + Duration::from_secs(offset * 60 * 60);
+            let reward = rewards.process_at(&hook(WebhookType::Upvote, weekend), now);
+            reward.unwrap().unwrap()
+        };
+
+        let reward = vote(0, false);
+        assert_eq!((reward.points, reward.balance.streak), (10, 1));
+        let reward = vote(12, true);
+        assert_eq!((reward.points, reward.balance.streak), (20, 1));
+        let reward = vote(24, false);
+        assert_eq!((reward.points, reward.balance.streak), (15, 2));
+        let reward = vote(48, false);
+        assert_eq!((reward.points, reward.balance.streak), (15, 3));
+        let reward = vote(96, false);
+        assert_eq!((reward.points, reward.balance.streak), (10, 1));
+        assert_eq!(reward.balance.points, 70);
+    }
+
+    #[test]
+    fn concurrent_votes() {
+        let rewards = Rewards::new(MemoryStore::new()).points(1);
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    for _ in 0..100 {
+                        let hook = hook(WebhookType::Upvote, false);
+                        rewards.process(&hook).unwrap();
+                    }
+                });
+            }
+        });
+        let balance = rewards.store().get(UserId(2)).unwrap();
+        assert_eq!(balance.points, 800);
+    }
+
+    #[test]
+    fn test_votes_excluded() {
+        let rewards = Rewards::new(MemoryStore::new());
+        let reward = rewards.process(&hook(WebhookType::Test, false)).unwrap();
+        assert_eq!(reward, None);
+
+        let rewards = rewards.include_test(true);
+        let reward = rewards.process(&hook(WebhookType::Test, false)).unwrap();
+        assert_eq!(reward.map(|r| r.points), Some(1));
+    }
+}
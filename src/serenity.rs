@@ -0,0 +1,302 @@
+//! Integration with [serenity](https://docs.rs/serenity).
+//!
+//! The [`DblHandler`] is an event handler that posts the server counts of serenity's cache
+//! with [`Client::update_stats`] at a fixed interval and makes vote events available in the
+//! [`TypeMap`] of the context data.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use dbl::serenity::{DblHandler, VoteEvents};
+//! use serenity::prelude::*;
+//!
+//! # async fn run(hook: dbl::types::Webhook) -> Result<(), Box<dyn std::error::Error>> {
+//! let client = dbl::Client::new("token".into())?;
+//! let handler = DblHandler::new(client);
+//!
+//! // Pass the votes of the webhook receiver to the handler.
+//! let votes = handler.votes();
+//! let _ = votes.send(hook);
+//!
+//! let intents = GatewayIntents::GUILDS;
+//! let mut client = serenity::Client::builder("discord token", intents)
+//!     .type_map_insert::<VoteEvents>(handler.votes())
+//!     .event_handler(handler)
+//!     .await?;
+//! client.start_autosharded().await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Other event handlers subscribe to the vote events with [`subscribe_votes`].
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use ::serenity::async_trait;
+use ::serenity::cache::Cache;
+use ::serenity::model::gateway::Ready;
+use ::serenity::model::id::GuildId;
+use ::serenity::prelude::{Context, EventHandler, RwLock, TypeMap, TypeMapKey};
+use ::serenity::utils::shard_id;
+use tokio::sync::broadcast;
+
+use crate::autopost;
+use crate::types::{BotId, ShardStats, ShardStatsBuilder, ShardStatsError, Webhook};
+use crate::{Client, Error};
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(30 * 60);
+const VOTE_CHANNEL_CAPACITY: usize = 64;
+
+type PostCallback = dyn Fn(Result<Vec<ShardStats>, Error>) + Send + Sync;
+
+/// [`TypeMapKey`] of the sender of the vote events.
+pub struct VoteEvents;
+
+impl TypeMapKey for VoteEvents {
+    type Value = broadcast::Sender<Webhook>;
+}
+
+/// Subscribes to the vote events of the context data.
+///
+/// Returns `None` if no [`VoteEvents`] sender was inserted into the context data.
+pub async fn subscribe_votes(ctx: &Context) -> Option<broadcast::Receiver<Webhook>> {
+    let data = ctx.data.read().await;
+    data.get::<VoteEvents>().map(|tx| tx.subscribe())
+}
+
+/// Event handler that autoposts the stats of the bot.
+///
+/// The autoposting starts with the first `Ready` event. The server counts are grouped by the
+/// shards of this process. If the process runs all shards the stats are posted as
+/// [`ShardStats::Shards`], otherwise one [`ShardStats::Shard`] is posted per shard of the
+/// process.
+pub struct DblHandler {
+    client: Client,
+    interval: Duration,
+    on_post: Option<Arc<PostCallback>>,
+    votes: broadcast::Sender<Webhook>,
+    shards: Arc<Mutex<BTreeSet<u32>>>,
+    started: AtomicBool,
+}
+
+impl DblHandler {
+    /// Constructs a new `DblHandler` that posts the stats every 30 minutes.
+    pub fn new(client: Client) -> Self {
+        let (votes, _) = broadcast::channel(VOTE_CHANNEL_CAPACITY);
+        DblHandler {
+            client,
+            interval: DEFAULT_INTERVAL,
+            on_post: None,
+            votes,
+            shards: Arc::new(Mutex::new(BTreeSet::new())),
+            started: AtomicBool::new(false),
+        }
+    }
+
+    /// Sets the interval of the autoposting. Defaults to 30 minutes.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets a callback that is called with the posted stats of every autopost.
+    pub fn on_post<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(Result<Vec<ShardStats>, Error>) + Send + Sync + 'static,
+    {
+        self.on_post = Some(Arc::new(callback));
+        self
+    }
+
+    /// Returns the sender of the vote events.
+    ///
+    /// Votes sent by the webhook receiver are passed to the subscribers of the
+    /// [`VoteEvents`] in the context data.
+    pub fn votes(&self) -> broadcast::Sender<Webhook> {
+        self.votes.clone()
+    }
+}
+
+#[async_trait]
+impl EventHandler for DblHandler {
+    async fn ready(&self, ctx: Context, ready: Ready) {
+        let shard = ready.shard.map_or(ctx.shard_id.0, |info| info.id.0);
+        let bot = BotId(ready.user.id.get());
+        self.on_ready(&ctx.data, Arc::clone(&ctx.cache), shard, bot)
+            .await;
+    }
+}
+
+impl DblHandler {
+    /// Registers the shard, inserts the [`VoteEvents`] and starts the autoposting once.
+    async fn on_ready(&self, data: &RwLock<TypeMap>, cache: Arc<Cache>, shard: u32, bot: BotId) {
+        self.shards.lock().unwrap().insert(shard);
+
+        {
+            let mut data = data.write().await;
+            data.entry::<VoteEvents>()
+                .or_insert_with(|| self.votes.clone());
+        }
+
+        if self.started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let client = self.client.clone();
+        let shards = Arc::clone(&self.shards);
+        let on_post = self.on_post.clone();
+        let period = self.interval;
+        tokio::spawn(async move {
+            let post = || post(&client, bot, &cache, &shards);
+            autopost::run(period, post, |result| {
+                if let Some(on_post) = &on_post {
+                    on_post(result);
+                }
+            })
+            .await
+        });
+    }
+}
+
+async fn post(
+    client: &Client,
+    bot: BotId,
+    cache: &Cache,
+    shards: &Mutex<BTreeSet<u32>>,
+) -> Result<Vec<ShardStats>, Error> {
+    let shards = shards.lock().unwrap().clone();
+    let stats = shard_stats(cache.guilds(), &shards, cache.shard_count());
+    let stats = stats.map_err(Error::InvalidStats)?;
+    for stats in &stats {
+        client.update_stats(bot, stats.clone()).await?;
+    }
+    Ok(stats)
+}
+
+/// Builds the stats from the guilds of the cache and the shards of this process.
+///
+/// The counts of a process without all shards are only partial, they are returned per shard
+/// instead of as the total.
+fn shard_stats<I>(
+    guilds: I,
+    shards: &BTreeSet<u32>,
+    shard_count: u32,
+) -> Result<Vec<ShardStats>, ShardStatsError>
+where
+    I: IntoIterator<Item = GuildId>,
+{
+    let shard_count = shard_count.max(1);
+    let mut counts = shards
+        .iter()
+        .map(|&shard| (shard, 0))
+        .collect::<BTreeMap<_, _>>();
+    for guild in guilds {
+        *counts.entry(shard_id(guild, shard_count)).or_insert(0) += 1;
+    }
+
+    let mut builder = ShardStatsBuilder::new().shard_count(u64::from(shard_count));
+    for (&shard, &count) in &counts {
+        builder.insert(u64::from(shard), count);
+    }
+    match builder.build_shards() {
+        Ok(stats) => Ok(vec![stats]),
+        Err(ShardStatsError::MissingShards(_)) => {
+            let stats = counts
+                .into_iter()
+                .map(|(shard, server_count)| ShardStats::Shard {
+                    server_count,
+                    shard_id: u64::from(shard),
+                    shard_count: u64::from(shard_count),
+                })
+                .collect();
+            Ok(stats)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::test_util::{self, Redirect};
+    use crate::types::WebhookType;
+
+    fn guild(shard: u64) -> GuildId {
+        GuildId::new((shard << 22) | 1)
+    }
+
+    #[test]
+    fn stats_from_cache() {
+        let guilds = vec![guild(0), guild(1), guild(1), guild(3)];
+
+        let shards = (0..4).collect();
+        let stats = shard_stats(guilds.clone(), &shards, 4);
+        assert_eq!(
+            stats,
+            Ok(vec![ShardStats::Shards {
+                shards: vec![1, 2, 0, 1]
+            }])
+        );
+
+        let shards = (0..2).collect();
+        let guilds = guilds.into_iter().filter(|g| shard_id(*g, 4) < 2);
+        let stats = shard_stats(guilds, &shards, 4);
+        assert_eq!(
+            stats,
+            Ok(vec![
+                ShardStats::Shard {
+                    server_count: 1,
+                    shard_id: 0,
+                    shard_count: 4,
+                },
+                ShardStats::Shard {
+                    server_count: 2,
+                    shard_id: 1,
+                    shard_count: 4,
+                },
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn ready_starts_autopost() {
+        let (port, server) = test_util::serve(vec![("200 OK", "")]);
+        let client = Client::new("token".into())
+            .unwrap()
+            .with_hook(Redirect(port));
+        let (tx, rx) = mpsc::channel();
+        let tx = Mutex::new(tx);
+        let handler = DblHandler::new(client)
+            .interval(Duration::from_millis(50))
+            .on_post(move |result| {
+                let _ = tx.lock().unwrap().send(result);
+            });
+        let data = RwLock::new(TypeMap::new());
+        let cache = Arc::new(Cache::new());
+
+        handler
+            .on_ready(&data, Arc::clone(&cache), 0, BotId(1))
+            .await;
+        handler.on_ready(&data, cache, 0, BotId(1)).await;
+
+        let mut votes = data.read().await.get::<VoteEvents>().unwrap().subscribe();
+        let hook = Webhook::new(1, 2, WebhookType::Test);
+        handler.votes().send(hook.clone()).unwrap();
+        assert_eq!(votes.recv().await.unwrap(), hook);
+
+        let stats = tokio::task::spawn_blocking(move || rx.recv().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(stats.unwrap(), [ShardStats::Shards { shards: vec![0] }]);
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].request_line, "POST /api/bots/1/stats HTTP/1.1");
+        assert_eq!(requests[0].body, br#"{"shards":[0]}"#);
+    }
+}
@@ -0,0 +1,82 @@
+//! Minimal HTTP stand-in for tests.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::thread::{self, JoinHandle};
+
+use crate::hook::Hook;
+
+/// Hook that redirects the requests of the client to the stand-in.
+pub struct Redirect(pub u16);
+
+impl Hook for Redirect {
+    fn before_send(&self, req: &mut reqwest::Request) {
+        let url = req.url_mut();
+        url.set_scheme("http").unwrap();
+        url.set_host(Some("127.0.0.1")).unwrap();
+        url.set_port(Some(self.0)).unwrap();
+    }
+}
+
+/// Request received by the stand-in.
+pub struct Request {
+    pub request_line: String,
+    /// Header lines with lowercase names and values.
+    pub headers: Vec<String>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        let prefix = format!("{}: ", name);
+        self.headers.iter().find_map(|h| h.strip_prefix(&prefix))
+    }
+}
+
+/// Serves one connection per response and returns the received requests.
+///
+/// Each response is a status line with optional headers, e.g. `"200 OK"`, and a body.
+pub fn serve(responses: Vec<(&'static str, &'static str)>) -> (u16, JoinHandle<Vec<Request>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let handle = thread::spawn(move || {
+        let mut requests = vec![];
+        for (status, body) in responses {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut headers = vec![];
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                headers.push(line.trim_end().to_lowercase());
+            }
+            let mut req = Request {
+                request_line: request_line.trim_end().to_string(),
+                headers,
+                body: vec![],
+            };
+            let len = req
+                .header("content-length")
+                .map_or(0, |v| v.parse().unwrap());
+            req.body.resize(len, 0);
+            reader.read_exact(&mut req.body).unwrap();
+
+            let resp = format!(
+                "HTTP/1.1 {}\r\nconnection: close\r\ncontent-length: {}\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            reader.get_mut().write_all(resp.as_bytes()).unwrap();
+            requests.push(req);
+        }
+        requests
+    });
+    (port, handle)
+}
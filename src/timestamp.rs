@@ -0,0 +1,25 @@
+//! Timestamp types for the raw date strings of the API.
+//!
+//! Implementations are provided for `chrono::DateTime<Utc>` with the `chrono` feature and for
+//! `time::OffsetDateTime` with the `time` feature.
+
+/// Timestamp type that can be parsed from the RFC 3339 date strings of the API.
+pub trait Timestamp: Sized {
+    /// Parses a RFC 3339 date string, e.g. `2019-05-27T12:00:00.000Z`.
+    fn parse_rfc3339(s: &str) -> Option<Self>;
+}
+
+#[cfg(feature = "chrono")]
+impl Timestamp for chrono::DateTime<chrono::Utc> {
+    fn parse_rfc3339(s: &str) -> Option<Self> {
+        let date = chrono::DateTime::parse_from_rfc3339(s).ok()?;
+        Some(date.with_timezone(&chrono::Utc))
+    }
+}
+
+#[cfg(feature = "time")]
+impl Timestamp for time::OffsetDateTime {
+    fn parse_rfc3339(s: &str) -> Option<Self> {
+        time::OffsetDateTime::parse(s, &time::format_description::well_known::Rfc3339).ok()
+    }
+}
@@ -0,0 +1,235 @@
+//! Integration with [twilight](https://docs.rs/twilight).
+//!
+//! The [`StatsPoster`] keeps the guilds of every shard from the `Ready`, `GuildCreate` and
+//! `GuildDelete` events of `twilight-gateway` and posts the server counts with
+//! [`Client::update_stats`] at a fixed interval.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use dbl::twilight::StatsPoster;
+//! # use twilight_model::gateway::{event::Event, ShardId};
+//!
+//! # async fn run(events: Vec<(ShardId, Event)>) -> Result<(), dbl::Error> {
+//! let client = dbl::Client::new("token".into())?;
+//! let poster = StatsPoster::new(client, 565_030_624_499_466_240);
+//!
+//! let handle = poster.clone();
+//! tokio::spawn(async move {
+//!     handle
+//!         .run(|result| {
+//!             if let Err(e) = result {
+//!                 eprintln!("failed to post stats: {}", e);
+//!             }
+//!         })
+//!         .await
+//! });
+//!
+//! // Pass the events of all shards, e.g. from `shard.next_event(..)`.
+//! for (shard, event) in events {
+//!     poster.process(shard, &event);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use twilight_model::gateway::event::Event;
+use twilight_model::gateway::payload::incoming::GuildCreate;
+use twilight_model::gateway::ShardId;
+use twilight_model::id::marker::GuildMarker;
+use twilight_model::id::Id;
+
+use crate::autopost;
+use crate::types::{BotId, ShardStats, ShardStatsBuilder, ShardStatsError};
+use crate::{Client, Error};
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Default)]
+struct State {
+    shard_count: Option<u32>,
+    guilds: HashMap<u32, HashSet<Id<GuildMarker>>>,
+}
+
+/// Tracker of the guilds per shard that posts the server counts.
+///
+/// The stats are only posted as [`ShardStats::Shards`] once the events of all shards are
+/// processed. Until then, building the stats fails with [`ShardStatsError::MissingShards`].
+/// Cloned instances share the same guilds.
+#[derive(Clone)]
+pub struct StatsPoster {
+    client: Client,
+    bot: BotId,
+    interval: Duration,
+    state: Arc<Mutex<State>>,
+}
+
+impl StatsPoster {
+    /// Constructs a new `StatsPoster` for the stats of `bot`.
+    pub fn new<T: Into<BotId>>(client: Client, bot: T) -> Self {
+        StatsPoster {
+            client,
+            bot: bot.into(),
+            interval: DEFAULT_INTERVAL,
+            state: Arc::new(Mutex::new(State::default())),
+        }
+    }
+
+    /// Sets the interval of [`run`](StatsPoster::run). Defaults to 30 minutes.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Updates the guilds of a shard from a gateway event.
+    ///
+    /// A `Ready` event replaces the guilds of the shard. Events other than `Ready`,
+    /// `GuildCreate` and `GuildDelete` are ignored.
+    pub fn process(&self, shard: ShardId, event: &Event) {
+        let mut state = self.state.lock().unwrap();
+        match event {
+            Event::Ready(ready) => {
+                let shard = ready.shard.unwrap_or(shard);
+                state.shard_count = Some(shard.total());
+                let guilds = ready.guilds.iter().map(|g| g.id).collect();
+                state.guilds.insert(shard.number(), guilds);
+            }
+            Event::GuildCreate(guild) => {
+                let id = match &**guild {
+                    GuildCreate::Available(guild) => guild.id,
+                    GuildCreate::Unavailable(guild) => guild.id,
+                };
+                state.shard_count.get_or_insert(shard.total());
+                let guilds = state.guilds.entry(shard.number()).or_default();
+                guilds.insert(id);
+            }
+            // Unavailable guilds are still joined, only count removals.
+            Event::GuildDelete(guild) if guild.unavailable != Some(true) => {
+                if let Some(guilds) = state.guilds.get_mut(&shard.number()) {
+                    guilds.remove(&guild.id);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the number of guilds of a shard.
+    pub fn guild_count(&self, shard: u32) -> Option<u64> {
+        let state = self.state.lock().unwrap();
+        state.guilds.get(&shard).map(|guilds| guilds.len() as u64)
+    }
+
+    /// Builds the stats from the guilds of all shards.
+    pub fn stats(&self) -> Result<ShardStats, ShardStatsError> {
+        let state = self.state.lock().unwrap();
+        let mut builder = ShardStatsBuilder::new();
+        if let Some(count) = state.shard_count {
+            builder = builder.shard_count(u64::from(count));
+        }
+        for (&shard, guilds) in &state.guilds {
+            builder.insert(u64::from(shard), guilds.len() as u64);
+        }
+        builder.build_shards()
+    }
+
+    /// Posts the stats and returns them.
+    pub async fn post(&self) -> Result<ShardStats, Error> {
+        let stats = self.stats().map_err(Error::InvalidStats)?;
+        self.client.update_stats(self.bot, stats.clone()).await?;
+        Ok(stats)
+    }
+
+    /// Posts the stats at the configured interval.
+    ///
+    /// The callback is called with the result of every post. The first post happens after
+    /// the first interval to give the shards time to receive their guilds.
+    pub async fn run<F>(&self, callback: F)
+    where
+        F: FnMut(Result<ShardStats, Error>),
+    {
+        autopost::run(self.interval, || self.post(), callback).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use twilight_model::gateway::payload::incoming::{GuildDelete, Ready};
+    use twilight_model::guild::UnavailableGuild;
+
+    use super::*;
+    use crate::test_util::{self, Redirect};
+
+    fn ready(shard: [u32; 2], guilds: &[u64]) -> Event {
+        let guilds = guilds
+            .iter()
+            .map(|id| serde_json::json!({"id": id.to_string(), "unavailable": true}))
+            .collect::<Vec<_>>();
+        let ready = serde_json::json!({
+            "application": {"flags": 0, "id": "1"},
+            "guilds": guilds,
+            "resume_gateway_url": "wss://gateway.discord.gg",
+            "session_id": "session",
+            "shard": shard,
+            "user": {
+                "accent_color": null,
+                "avatar": null,
+                "banner": null,
+                "bot": true,
+                "discriminator": "0",
+                "id": "1",
+                "mfa_enabled": false,
+                "username": "bot"
+            },
+            "v": 10
+        });
+        let ready: Ready = serde_json::from_value(ready).unwrap();
+        Event::Ready(Box::new(ready))
+    }
+
+    fn guild_create(id: u64) -> Event {
+        let guild = UnavailableGuild {
+            id: Id::new(id),
+            unavailable: false,
+        };
+        Event::GuildCreate(Box::new(GuildCreate::Unavailable(guild)))
+    }
+
+    fn guild_delete(id: u64, unavailable: Option<bool>) -> Event {
+        Event::GuildDelete(GuildDelete {
+            id: Id::new(id),
+            unavailable,
+        })
+    }
+
+    #[tokio::test]
+    async fn synthetic_events() {
+        let (port, server) = test_util::serve(vec![("200 OK", "")]);
+        let client = Client::new("token".into())
+            .unwrap()
+            .with_hook(Redirect(port));
+        let poster = StatsPoster::new(client, 1);
+        let (shard0, shard1) = (ShardId::new(0, 2), ShardId::new(1, 2));
+
+        poster.process(shard0, &ready([0, 2], &[10, 12]));
+        poster.process(shard0, &guild_create(10));
+        poster.process(shard0, &guild_create(14));
+        assert_eq!(poster.guild_count(0), Some(3));
+        let err = ShardStatsError::MissingShards(vec![1]);
+        assert_eq!(poster.stats(), Err(err));
+
+        poster.process(shard1, &ready([1, 2], &[11]));
+        poster.process(shard1, &guild_create(13));
+        poster.process(shard0, &guild_delete(12, Some(true)));
+        poster.process(shard1, &guild_delete(11, None));
+
+        let stats = poster.post().await.unwrap();
+        assert_eq!(stats, ShardStats::Shards { shards: vec![3, 1] });
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests[0].body, br#"{"shards":[3,1]}"#);
+    }
+}
@@ -1,29 +1,46 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::timestamp::Timestamp;
+
+pub mod v1;
 
 /// Newtype for bot ids.
-#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct BotId(pub u64);
 /// Newtype for user ids.
-#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct UserId(pub u64);
 /// Newtype for guild ids.
-#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct GuildId(pub u64);
 
-/// Basic user information returned by [`Client::votes`](super::Client::votes).
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+/// Basic user information returned by
+#[cfg_attr(feature = "client", doc = "[`Client::votes`](super::Client::votes).")]
+#[cfg_attr(not(feature = "client"), doc = "`Client::votes`.")]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+#[serde(default)]
 pub struct User {
     pub id: UserId,
     pub username: String,
     pub discriminator: String,
     pub avatar: Option<String>,
+    /// Unknown fields returned by the API.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-/// Detailed user information returned by [`Client::user`](super::Client::user).
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
-#[serde(rename_all = "camelCase")]
+/// Detailed user information returned by
+#[cfg_attr(feature = "client", doc = "[`Client::user`](super::Client::user).")]
+#[cfg_attr(not(feature = "client"), doc = "`Client::user`.")]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+#[serde(default, rename_all = "camelCase")]
 pub struct DetailedUser {
     pub id: UserId,
     pub username: String,
@@ -41,21 +58,30 @@ pub struct DetailedUser {
     pub mod_: bool,
     pub web_mod: bool,
     pub admin: bool,
+    /// Unknown fields returned by the API.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 /// Social media accounts of the user.
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+#[serde(default)]
 pub struct Social {
     pub github: Option<String>,
     pub instagram: Option<String>,
     pub reddit: Option<String>,
     pub twitter: Option<String>,
     pub youtube: Option<String>,
+    /// Unknown fields returned by the API.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 /// Information about a bot.
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
-#[serde(rename_all = "camelCase")]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+#[serde(default, rename_all = "camelCase")]
 pub struct Bot {
     pub id: BotId,
     pub username: String,
@@ -83,18 +109,96 @@ pub struct Bot {
     pub shards: Vec<u64>,
     pub points: u64,
     pub monthly_points: u64,
+    /// Unknown fields returned by the API.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Bot {
+    /// Parses the [`date`](Bot::date) the bot was added to top.gg.
+    ///
+    /// Requires the `chrono` or the `time` feature for an implementation of [`Timestamp`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn run(bot: dbl::types::Bot) {
+    /// # #[cfg(feature = "chrono")]
+    /// let date: Option<chrono::DateTime<chrono::Utc>> = bot.date_as();
+    /// # }
+    /// ```
+    pub fn date_as<T: Timestamp>(&self) -> Option<T> {
+        T::parse_rfc3339(&self.date)
+    }
 }
 
 /// Bot's sharding stats.
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+#[serde(default)]
 pub struct Stats {
     pub server_count: Option<u64>,
     pub shards: Vec<u64>,
     pub shard_count: Option<u64>,
+    /// Unknown fields returned by the API.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Vote status of a user returned by
+#[cfg_attr(
+    feature = "client",
+    doc = "[`Client::vote_status`](super::Client::vote_status)."
+)]
+#[cfg_attr(not(feature = "client"), doc = "`Client::vote_status`.")]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct VoteStatus {
+    /// Whether the user has an active vote.
+    pub voted: bool,
+    /// Time of the vote, if provided by the API.
+    pub voted_at: Option<String>,
+    /// Whether the vote was a weekend vote, if provided by the API.
+    pub is_weekend: Option<bool>,
+    /// Time when the user can vote again, if provided by the API.
+    pub next_vote_at: Option<String>,
+}
+
+impl VoteStatus {
+    /// Parses the [`voted_at`](VoteStatus::voted_at) time of the vote.
+    ///
+    /// Requires the `chrono` or the `time` feature for an implementation of [`Timestamp`].
+    pub fn voted_at_as<T: Timestamp>(&self) -> Option<T> {
+        self.voted_at.as_deref().and_then(T::parse_rfc3339)
+    }
+
+    /// Parses the [`next_vote_at`](VoteStatus::next_vote_at) time when the user can vote again.
+    ///
+    /// Requires the `chrono` or the `time` feature for an implementation of [`Timestamp`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "chrono")]
+    /// # fn run(status: dbl::types::VoteStatus, now: chrono::DateTime<chrono::Utc>) {
+    /// use chrono::{DateTime, Utc};
+    ///
+    /// let next: Option<DateTime<Utc>> = status.next_vote_at_as();
+    /// if next.map_or(true, |next| next <= now) {
+    ///     println!("the user can vote again");
+    /// }
+    /// # }
+    /// ```
+    pub fn next_vote_at_as<T: Timestamp>(&self) -> Option<T> {
+        self.next_vote_at.as_deref().and_then(T::parse_rfc3339)
+    }
 }
 
 /// Used to update one or more sharding stats.
-#[derive(Debug, Serialize)]
+///
+/// Use the [`ShardStatsBuilder`] to build consistent stats from per-shard server counts.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
 #[serde(untagged)]
 pub enum ShardStats {
     Cumulative {
@@ -111,7 +215,322 @@ pub enum ShardStats {
     },
 }
 
-/// Used for filtering the bot search.
+impl ShardStats {
+    /// Checks the stats for inconsistencies.
+    ///
+    /// Invalid stats are rejected with this error before sending them by
+    #[cfg_attr(
+        feature = "client",
+        doc = "[`Client::update_stats`](super::Client::update_stats)."
+    )]
+    #[cfg_attr(not(feature = "client"), doc = "`Client::update_stats`.")]
+    pub fn validate(&self) -> Result<(), ShardStatsError> {
+        match *self {
+            ShardStats::Cumulative {
+                shard_count: Some(0),
+                ..
+            }
+            | ShardStats::Shard { shard_count: 0, .. } => Err(ShardStatsError::ZeroShardCount),
+            ShardStats::Shard {
+                shard_id,
+                shard_count,
+                ..
+            } if shard_id >= shard_count => Err(ShardStatsError::InvalidShardId {
+                shard_id,
+                shard_count,
+            }),
+            ShardStats::Shards { ref shards } if shards.is_empty() => Err(ShardStatsError::Empty),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Errors of invalid [`ShardStats`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ShardStatsError {
+    /// No server counts were provided.
+    Empty,
+    /// The shard count is zero.
+    ZeroShardCount,
+    /// The shard id isn't lower than the shard count.
+    InvalidShardId { shard_id: u64, shard_count: u64 },
+    /// The stats of a single shard can't be posted to the v1 API.
+    SingleShard,
+    /// The total number of shards isn't known.
+    UnknownShardCount,
+    /// The server counts of these shards are missing.
+    MissingShards(Vec<u64>),
+    /// The server counts of these shards are outdated.
+    StaleShards(Vec<u64>),
+}
+
+impl std::error::Error for ShardStatsError {}
+
+impl fmt::Display for ShardStatsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShardStatsError::Empty => f.write_str("No server counts provided"),
+            ShardStatsError::ZeroShardCount => f.write_str("Shard count must not be zero"),
+            ShardStatsError::InvalidShardId {
+                shard_id,
+                shard_count,
+            } => write!(
+                f,
+                "Shard id {} out of range for shard count {}",
+                shard_id, shard_count
+            ),
+            ShardStatsError::SingleShard => {
+                f.write_str("Stats of a single shard are not supported by the v1 API")
+            }
+            ShardStatsError::UnknownShardCount => f.write_str("Shard count is unknown"),
+            ShardStatsError::MissingShards(shards) => {
+                write!(f, "Server counts of shards {:?} are missing", shards)
+            }
+            ShardStatsError::StaleShards(shards) => {
+                write!(f, "Server counts of shards {:?} are outdated", shards)
+            }
+        }
+    }
+}
+
+/// Builder of [`ShardStats`] from per-shard server counts.
+///
+/// The counts of the shards can be gathered from different tasks or processes. The builder
+/// produces [`ShardStats::Shards`] if the counts of all shards are known, and
+/// [`ShardStats::Cumulative`] with the total shard count otherwise.
+///
+/// # Example
+///
+/// ```
+/// use dbl::types::{ShardStats, ShardStatsBuilder};
+///
+/// let mut builder = ShardStatsBuilder::new().shard_count(2);
+/// builder.insert(1, 20);
+/// builder.insert(0, 10);
+///
+/// let stats = builder.build().unwrap();
+/// assert_eq!(stats, ShardStats::Shards { shards: vec![10, 20] });
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ShardStatsBuilder {
+    shard_count: Option<u64>,
+    shards: BTreeMap<u64, u64>,
+}
+
+impl ShardStatsBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the total number of shards of the bot.
+    ///
+    /// Without the shard count, the builder can't tell whether all shards are known and
+    /// always produces [`ShardStats::Cumulative`].
+    pub fn shard_count(mut self, count: u64) -> Self {
+        self.shard_count = Some(count);
+        self
+    }
+
+    /// Sets the server count of a shard.
+    pub fn shard(mut self, shard_id: u64, server_count: u64) -> Self {
+        self.insert(shard_id, server_count);
+        self
+    }
+
+    /// Sets the server count of a shard, replacing a previously reported count.
+    pub fn insert(&mut self, shard_id: u64, server_count: u64) {
+        self.shards.insert(shard_id, server_count);
+    }
+
+    /// Removes the server count of a shard.
+    pub fn remove(&mut self, shard_id: u64) -> Option<u64> {
+        self.shards.remove(&shard_id)
+    }
+
+    /// Validates the server counts and builds the stats.
+    pub fn build(&self) -> Result<ShardStats, ShardStatsError> {
+        if self.shards.is_empty() {
+            return Err(ShardStatsError::Empty);
+        }
+        let shard_count = match self.shard_count {
+            Some(0) => return Err(ShardStatsError::ZeroShardCount),
+            Some(shard_count) => shard_count,
+            None => {
+                return Ok(ShardStats::Cumulative {
+                    server_count: self.shards.values().sum(),
+                    shard_count: None,
+                })
+            }
+        };
+        if let Some((&shard_id, _)) = self.shards.range(shard_count..).next() {
+            return Err(ShardStatsError::InvalidShardId {
+                shard_id,
+                shard_count,
+            });
+        }
+
+        let stats = if self.shards.len() as u64 == shard_count {
+            ShardStats::Shards {
+                shards: self.shards.values().copied().collect(),
+            }
+        } else {
+            ShardStats::Cumulative {
+                server_count: self.shards.values().sum(),
+                shard_count: Some(shard_count),
+            }
+        };
+        Ok(stats)
+    }
+
+    /// Validates the server counts and builds [`ShardStats::Shards`].
+    ///
+    /// Unlike [`build`](ShardStatsBuilder::build), the stats are never the partial sum of the
+    /// known shards. Returns [`ShardStatsError::MissingShards`] if the counts of some shards
+    /// aren't known.
+    pub fn build_shards(&self) -> Result<ShardStats, ShardStatsError> {
+        let stats = self.build()?;
+        let shard_count = match (&stats, self.shard_count) {
+            (ShardStats::Shards { .. }, _) => return Ok(stats),
+            (_, Some(shard_count)) => shard_count,
+            (_, None) => return Err(ShardStatsError::UnknownShardCount),
+        };
+        let missing = (0..shard_count)
+            .filter(|id| !self.shards.contains_key(id))
+            .collect();
+        Err(ShardStatsError::MissingShards(missing))
+    }
+}
+
+/// Information about a server.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+#[serde(default, rename_all = "camelCase")]
+pub struct Server {
+    pub id: GuildId,
+    pub name: String,
+    pub icon: Option<String>,
+    #[serde(rename = "shortdesc")]
+    pub short_desc: String,
+    #[serde(rename = "longdesc")]
+    pub long_desc: Option<String>,
+    pub tags: Vec<String>,
+    pub website: Option<String>,
+    pub invite: Option<String>,
+    pub owners: Vec<UserId>,
+    pub date: String,
+    pub vanity: Option<String>,
+    pub points: u64,
+    pub monthly_points: u64,
+    /// Unknown fields returned by the API.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Server's stats returned by
+#[cfg_attr(
+    feature = "client",
+    doc = "[`Client::server_stats`](super::Client::server_stats)."
+)]
+#[cfg_attr(not(feature = "client"), doc = "`Client::server_stats`.")]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+#[serde(default, rename_all = "camelCase")]
+pub struct ServerStats {
+    pub member_count: Option<u64>,
+    pub online_count: Option<u64>,
+    pub points: u64,
+    pub monthly_points: u64,
+    /// Unknown fields returned by the API.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Review of a bot returned by
+#[cfg_attr(
+    feature = "client",
+    doc = "[`Client::reviews`](super::Client::reviews)."
+)]
+#[cfg_attr(not(feature = "client"), doc = "`Client::reviews`.")]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+#[serde(default)]
+pub struct Review {
+    pub id: String,
+    pub author: UserId,
+    /// Rating from 1 to 5.
+    pub rating: u8,
+    pub content: String,
+    pub timestamp: String,
+    #[serde(default)]
+    pub replies: Vec<ReviewReply>,
+    /// Unknown fields returned by the API.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Review {
+    /// Parses the [`timestamp`](Review::timestamp) of the review.
+    ///
+    /// Requires the `chrono` or the `time` feature for an implementation of [`Timestamp`].
+    pub fn timestamp_as<T: Timestamp>(&self) -> Option<T> {
+        T::parse_rfc3339(&self.timestamp)
+    }
+}
+
+/// Reply to a [`Review`].
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+#[serde(default)]
+pub struct ReviewReply {
+    pub id: String,
+    pub author: UserId,
+    pub content: String,
+    pub timestamp: String,
+    /// Unknown fields returned by the API.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Aggregated ratings of a bot's reviews.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct RatingSummary {
+    /// Number of reviews with a rating.
+    pub count: u64,
+    /// Average rating, `0.0` if there are no reviews.
+    pub average: f64,
+    /// Number of reviews per rating, from 1 to 5 stars.
+    pub distribution: [u64; 5],
+}
+
+impl RatingSummary {
+    /// Aggregates the ratings of the reviews.
+    ///
+    /// Ratings outside of `1..=5`, e.g. the `0` of a review without rating, are skipped.
+    pub fn from_reviews<'a, I>(reviews: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Review>,
+    {
+        let mut summary = RatingSummary::default();
+        let mut sum = 0;
+        for review in reviews {
+            let rating = review.rating;
+            if !(1..=5).contains(&rating) {
+                continue;
+            }
+            summary.count += 1;
+            summary.distribution[usize::from(rating - 1)] += 1;
+            sum += u64::from(rating);
+        }
+        if summary.count > 0 {
+            summary.average = sum as f64 / summary.count as f64;
+        }
+        summary
+    }
+}
+
+/// Used for filtering the bot and server search.
 pub struct Filter(pub(crate) HashMap<&'static str, String>);
 
 impl Default for Filter {
@@ -155,18 +574,46 @@ impl Filter {
     }
 }
 
-/// Search result returned by [`Client::search`](super::Client::search).
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+/// Search result returned by
+#[cfg_attr(feature = "client", doc = "[`Client::search`](super::Client::search).")]
+#[cfg_attr(not(feature = "client"), doc = "`Client::search`.")]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+#[serde(default)]
 pub struct Listing {
     pub results: Vec<Bot>,
     pub limit: u64,
     pub offset: u64,
     pub count: u64,
     pub total: u64,
+    /// Unknown fields returned by the API.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Search result returned by
+#[cfg_attr(
+    feature = "client",
+    doc = "[`Client::search_servers`](super::Client::search_servers)."
+)]
+#[cfg_attr(not(feature = "client"), doc = "`Client::search_servers`.")]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+#[serde(default)]
+pub struct ServerListing {
+    pub results: Vec<Server>,
+    pub limit: u64,
+    pub offset: u64,
+    pub count: u64,
+    pub total: u64,
+    /// Unknown fields returned by the API.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 /// Vote received via webhook.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
 #[serde(rename_all = "camelCase")]
 pub struct Webhook {
     pub bot: BotId,
@@ -177,59 +624,252 @@ pub struct Webhook {
     pub is_weekend: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub query: Option<String>,
+    /// Unknown fields returned by the API.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-/// Type of vote received via webhook.
+/// Vote for a server received via webhook.
+///
+/// Server votes are verified and parsed with
+#[cfg_attr(
+    feature = "webhook",
+    doc = "[`Receiver::verify_as`](crate::webhook::Receiver::verify_as)."
+)]
+#[cfg_attr(not(feature = "webhook"), doc = "`Receiver::verify_as`.")]
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
-#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub struct ServerWebhook {
+    pub guild: GuildId,
+    pub user: UserId,
+    #[serde(rename = "type")]
+    pub kind: WebhookType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// Unknown fields returned by the API.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Type of vote received via webhook.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
 pub enum WebhookType {
     Upvote,
     Test,
+    /// Type unknown to this version of the crate.
+    Unknown(String),
+}
+
+impl WebhookType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            WebhookType::Upvote => "upvote",
+            WebhookType::Test => "test",
+            WebhookType::Unknown(kind) => kind,
+        }
+    }
+}
+
+impl From<&str> for WebhookType {
+    fn from(kind: &str) -> Self {
+        match kind {
+            "upvote" => WebhookType::Upvote,
+            "test" => WebhookType::Test,
+            kind => WebhookType::Unknown(kind.to_string()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for WebhookType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        let kind = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        Ok(WebhookType::from(&*kind))
+    }
+}
+
+impl Serialize for WebhookType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
 }
 
 impl Webhook {
+    /// Constructs a new `Webhook` vote.
+    pub fn new<B, U>(bot: B, user: U, kind: WebhookType) -> Self
+    where
+        B: Into<BotId>,
+        U: Into<UserId>,
+    {
+        Webhook {
+            bot: bot.into(),
+            user: user.into(),
+            kind,
+            is_weekend: false,
+            query: None,
+            extra: Map::new(),
+        }
+    }
+
     pub fn is_test(&self) -> bool {
         std::matches!(self.kind, WebhookType::Test)
     }
-}
 
-impl ::std::ops::Index<usize> for Listing {
-    type Output = Bot;
+    /// Returns the parsed query parameters of the vote link.
+    ///
+    /// See [`VoteLink`](crate::links::VoteLink) for building vote links with query parameters.
+    pub fn query_pairs(&self) -> HashMap<String, String> {
+        query_pairs(self.query.as_deref())
+    }
 
-    fn index(&self, index: usize) -> &Self::Output {
-        &self.results[index]
+    /// Deserializes the query parameters of the vote link into `T`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use dbl::types::{Webhook, WebhookType};
+    /// #[derive(serde::Deserialize)]
+    /// struct Campaign {
+    ///     source: String,
+    ///     id: u32,
+    /// }
+    ///
+    /// # let mut hook = Webhook::new(1, 2, WebhookType::Upvote);
+    /// # hook.query = Some("?source=reddit&id=4".into());
+    /// let campaign: Campaign = hook.query_as().unwrap();
+    /// assert_eq!(campaign.source, "reddit");
+    /// assert_eq!(campaign.id, 4);
+    /// ```
+    #[cfg(feature = "query")]
+    pub fn query_as<T>(&self) -> Result<T, serde_urlencoded::de::Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        query_as(self.query.as_deref())
     }
 }
 
-impl IntoIterator for Listing {
-    type Item = Bot;
-    type IntoIter = ::std::vec::IntoIter<Bot>;
+impl ServerWebhook {
+    /// Constructs a new `ServerWebhook` vote.
+    pub fn new<G, U>(guild: G, user: U, kind: WebhookType) -> Self
+    where
+        G: Into<GuildId>,
+        U: Into<UserId>,
+    {
+        ServerWebhook {
+            guild: guild.into(),
+            user: user.into(),
+            kind,
+            query: None,
+            extra: Map::new(),
+        }
+    }
+
+    pub fn is_test(&self) -> bool {
+        std::matches!(self.kind, WebhookType::Test)
+    }
+
+    /// Returns the parsed query parameters of the vote link.
+    pub fn query_pairs(&self) -> HashMap<String, String> {
+        query_pairs(self.query.as_deref())
+    }
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.results.into_iter()
+    /// Deserializes the query parameters of the vote link into `T`.
+    #[cfg(feature = "query")]
+    pub fn query_as<T>(&self) -> Result<T, serde_urlencoded::de::Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        query_as(self.query.as_deref())
     }
 }
 
-impl<'a> IntoIterator for &'a Listing {
-    type Item = &'a Bot;
-    type IntoIter = ::std::slice::Iter<'a, Bot>;
+fn query_pairs(query: Option<&str>) -> HashMap<String, String> {
+    let query = query.unwrap_or_default();
+    let query = query.strip_prefix('?').unwrap_or(query);
+    url::form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect()
+}
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.results.iter()
-    }
+#[cfg(feature = "query")]
+fn query_as<T>(query: Option<&str>) -> Result<T, serde_urlencoded::de::Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let query = query.unwrap_or_default();
+    serde_urlencoded::from_str(query.strip_prefix('?').unwrap_or(query))
+}
+
+macro_rules! impl_listing {
+    ($($type:ty => $item:ty),*) => {
+        $(
+            impl ::std::ops::Index<usize> for $type {
+                type Output = $item;
+
+                fn index(&self, index: usize) -> &Self::Output {
+                    &self.results[index]
+                }
+            }
+
+            impl IntoIterator for $type {
+                type Item = $item;
+                type IntoIter = ::std::vec::IntoIter<$item>;
+
+                fn into_iter(self) -> Self::IntoIter {
+                    self.results.into_iter()
+                }
+            }
+
+            impl<'a> IntoIterator for &'a $type {
+                type Item = &'a $item;
+                type IntoIter = ::std::slice::Iter<'a, $item>;
+
+                fn into_iter(self) -> Self::IntoIter {
+                    self.results.iter()
+                }
+            }
+        )*
+    };
 }
 
+impl_listing!(Listing => Bot, ServerListing => Server);
+
+#[cfg(feature = "client")]
+#[derive(Deserialize)]
+pub(crate) struct ReviewPage {
+    pub results: Vec<Review>,
+    pub total: u64,
+}
+
+#[cfg(feature = "client")]
 #[derive(Deserialize)]
 pub(crate) struct UserVoted {
     pub voted: u64,
 }
 
+#[cfg(feature = "client")]
+#[derive(Deserialize)]
+pub(crate) struct IsWeekend {
+    pub is_weekend: bool,
+}
+
+#[cfg(feature = "client")]
 #[derive(Deserialize)]
 #[serde(rename = "kebab-case")]
 pub(crate) struct Ratelimit {
     pub retry_after: u32,
 }
 
+/// Discord epoch, the first second of 2015, in milliseconds.
+const DISCORD_EPOCH: u64 = 1_420_070_400_000;
+
 macro_rules! impl_snowflake {
     ($($type:ty),*) => {
         $(
@@ -237,6 +877,12 @@ macro_rules! impl_snowflake {
                 pub fn as_u64(&self) -> u64 {
                     self.0
                 }
+
+                /// Returns the creation time encoded in the snowflake.
+                pub fn created_at(&self) -> SystemTime {
+                    let millis = (self.0 >> 22) + DISCORD_EPOCH;
+                    UNIX_EPOCH + Duration::from_millis(millis)
+                }
             }
 
             impl ::std::fmt::Display for $type {
@@ -265,6 +911,13 @@ macro_rules! impl_snowflake {
                             f.write_str("identifier")
                         }
 
+                        fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+                        where
+                            E: ::serde::de::Error,
+                        {
+                            Ok(v.into())
+                        }
+
                         fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
                         where
                             E: ::serde::de::Error,
@@ -275,7 +928,13 @@ macro_rules! impl_snowflake {
                         }
                     }
 
-                    deserializer.deserialize_str(Visitor)
+                    // Non-self-describing formats don't support `deserialize_any` and get the
+                    // string written by `Serialize`.
+                    if deserializer.is_human_readable() {
+                        deserializer.deserialize_any(Visitor)
+                    } else {
+                        deserializer.deserialize_str(Visitor)
+                    }
                 }
             }
 
@@ -297,69 +956,277 @@ impl_snowflake!(BotId, GuildId, UserId);
 mod tests {
     use super::*;
 
-    use serde_test::Token;
+    use serde_test::{Configure, Token};
 
     #[test]
     fn webhook_serde() {
-        let value = Webhook {
-            bot: BotId(1),
-            user: UserId(2),
-            kind: WebhookType::Test,
-            is_weekend: false,
-            query: None,
-        };
+        let value = Webhook::new(1, 2, WebhookType::Test);
 
         serde_test::assert_tokens(
-            &value,
+            &value.readable(),
             &[
-                Token::Struct {
-                    name: "Webhook",
-                    len: 4,
-                },
+                Token::Map { len: None },
                 Token::Str("bot"),
                 Token::Str("1"),
                 Token::Str("user"),
                 Token::Str("2"),
                 Token::Str("type"),
-                Token::UnitVariant {
-                    name: "WebhookType",
-                    variant: "test",
-                },
+                Token::Str("test"),
                 Token::Str("isWeekend"),
                 Token::Bool(false),
-                Token::StructEnd,
+                Token::MapEnd,
             ],
         );
     }
 
     #[test]
-    fn webhook_de_no_weekend() {
-        let value = Webhook {
-            bot: BotId(1),
-            user: UserId(2),
-            kind: WebhookType::Test,
-            is_weekend: false,
-            query: None,
+    fn webhook_query_pairs() {
+        let mut value = Webhook::new(1, 2, WebhookType::Upvote);
+        value.query = Some("?ref=home&name=a%20b".into());
+
+        let pairs = value.query_pairs();
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(pairs["ref"], "home");
+        assert_eq!(pairs["name"], "a b");
+    }
+
+    #[test]
+    fn server_webhook_de() {
+        let mut value = ServerWebhook::new(1, 2, WebhookType::Upvote);
+        value.query = Some("?a=b".into());
+
+        serde_test::assert_de_tokens(
+            &value.readable(),
+            &[
+                Token::Map { len: Some(4) },
+                Token::Str("guild"),
+                Token::Str("1"),
+                Token::Str("user"),
+                Token::Str("2"),
+                Token::Str("type"),
+                Token::Str("upvote"),
+                Token::Str("query"),
+                Token::Some,
+                Token::Str("?a=b"),
+                Token::MapEnd,
+            ],
+        );
+    }
+
+    #[test]
+    fn rating_summary() {
+        let review = |rating| Review {
+            rating,
+            ..Review::default()
+        };
+        let reviews = [review(5), review(4), review(5), review(2)];
+
+        let summary = RatingSummary::from_reviews(&reviews);
+        assert_eq!(summary.count, 4);
+        assert_eq!(summary.average, 4.0);
+        assert_eq!(summary.distribution, [0, 1, 0, 1, 2]);
+
+        let reviews = [review(0), review(4), review(6), review(2)];
+        let summary = RatingSummary::from_reviews(&reviews);
+        assert_eq!(summary.count, 2);
+        assert_eq!(summary.average, 3.0);
+        assert_eq!(summary.distribution, [0, 1, 0, 1, 0]);
+
+        let summary = RatingSummary::from_reviews(&[]);
+        assert_eq!(summary, RatingSummary::default());
+    }
+
+    #[test]
+    fn snowflake_de_numeric() {
+        serde_test::assert_de_tokens(&BotId(1).readable(), &[Token::Str("1")]);
+        serde_test::assert_de_tokens(&BotId(1).readable(), &[Token::U64(1)]);
+        serde_test::assert_tokens(&BotId(1).compact(), &[Token::Str("1")]);
+
+        let ids: Vec<UserId> = serde_json::from_str(r#"["1", 2]"#).unwrap();
+        assert_eq!(ids, [UserId(1), UserId(2)]);
+    }
+
+    #[test]
+    fn bot_round_trip() {
+        let json = serde_json::json!({
+            "id": "1",
+            "username": "foo",
+            "discriminator": "0",
+            "avatar": null,
+            "defAvatar": "",
+            "clientid": "1",
+            "lib": "serenity",
+            "prefix": "!",
+            "shortdesc": "bar",
+            "longdesc": null,
+            "tags": ["Moderation"],
+            "website": null,
+            "support": null,
+            "github": null,
+            "owners": ["2"],
+            "guilds": [],
+            "invite": null,
+            "date": "2019-05-27T12:00:00.000Z",
+            "certifiedBot": false,
+            "vanity": null,
+            "shards": [],
+            "points": 10,
+            "monthlyPoints": 2,
+            "donatebotguildid": ""
+        });
+
+        let bot: Bot = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&bot).unwrap(), json);
+    }
+
+    #[test]
+    fn shard_stats_builder() {
+        let builder = ShardStatsBuilder::new().shard_count(3).shard(2, 30);
+        assert_eq!(
+            builder.build(),
+            Ok(ShardStats::Cumulative {
+                server_count: 30,
+                shard_count: Some(3),
+            })
+        );
+        let err = ShardStatsError::MissingShards(vec![0, 1]);
+        assert_eq!(builder.build_shards(), Err(err));
+
+        let builder = builder.shard(0, 10).shard(1, 20).shard(1, 25);
+        let stats = builder.build().unwrap();
+        assert_eq!(
+            stats,
+            ShardStats::Shards {
+                shards: vec![10, 25, 30]
+            }
+        );
+        assert_eq!(stats.validate(), Ok(()));
+
+        let builder = builder.shard(3, 40);
+        let err = ShardStatsError::InvalidShardId {
+            shard_id: 3,
+            shard_count: 3,
+        };
+        assert_eq!(builder.build(), Err(err));
+
+        let builder = ShardStatsBuilder::new();
+        assert_eq!(builder.build(), Err(ShardStatsError::Empty));
+        let builder = builder.shard(1, 10).shard(4, 5);
+        let stats = builder.build().unwrap();
+        assert_eq!(
+            stats,
+            ShardStats::Cumulative {
+                server_count: 15,
+                shard_count: None,
+            }
+        );
+        let err = ShardStatsError::UnknownShardCount;
+        assert_eq!(builder.build_shards(), Err(err));
+    }
+
+    #[test]
+    fn shard_stats_validate() {
+        let stats = ShardStats::Shard {
+            server_count: 10,
+            shard_id: 2,
+            shard_count: 2,
+        };
+        let err = ShardStatsError::InvalidShardId {
+            shard_id: 2,
+            shard_count: 2,
         };
+        assert_eq!(stats.validate(), Err(err));
+
+        let stats = ShardStats::Cumulative {
+            server_count: 10,
+            shard_count: Some(0),
+        };
+        assert_eq!(stats.validate(), Err(ShardStatsError::ZeroShardCount));
+
+        let stats = ShardStats::Shards { shards: vec![] };
+        assert_eq!(stats.validate(), Err(ShardStatsError::Empty));
+    }
+
+    #[test]
+    fn snowflake_created_at() {
+        let created_at = BotId(565_030_624_499_466_240).created_at();
+        let millis = created_at.duration_since(UNIX_EPOCH).unwrap().as_millis();
+        assert_eq!(millis, 1_554_784_198_642);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn timestamp_chrono() {
+        use chrono::{DateTime, Utc};
+
+        let date = DateTime::<Utc>::parse_rfc3339("2019-05-27T12:00:00.000Z").unwrap();
+        assert_eq!(date.timestamp(), 1_558_958_400);
+
+        let status = VoteStatus {
+            voted: true,
+            voted_at: Some("2019-05-27T00:00:00.000Z".into()),
+            is_weekend: None,
+            next_vote_at: Some("2019-05-27T12:00:00.000Z".into()),
+        };
+        let voted_at = status.voted_at_as::<DateTime<Utc>>().unwrap();
+        assert_eq!(status.next_vote_at_as(), Some(date));
+        assert!(voted_at < date);
+        assert_eq!(
+            VoteStatus::default().next_vote_at_as::<DateTime<Utc>>(),
+            None
+        );
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn timestamp_time() {
+        use time::OffsetDateTime;
+
+        let date = OffsetDateTime::parse_rfc3339("2019-05-27T12:00:00.000Z").unwrap();
+        assert_eq!(date.unix_timestamp(), 1_558_958_400);
+
+        let vote = v1::Vote {
+            created_at: "2019-05-27T00:00:00Z".into(),
+            expires_at: "2019-05-27T12:00:00Z".into(),
+            ..v1::Vote::default()
+        };
+        assert_eq!(vote.expires_at_as(), Some(date));
+        assert!(vote.created_at_as::<OffsetDateTime>().unwrap() < date);
+    }
+
+    #[test]
+    fn webhook_de_no_weekend() {
+        let value = Webhook::new(1, 2, WebhookType::Test);
 
         serde_test::assert_de_tokens(
-            &value,
+            &value.readable(),
             &[
-                Token::Struct {
-                    name: "Webhook",
-                    len: 3,
-                },
+                Token::Map { len: Some(3) },
                 Token::Str("bot"),
                 Token::Str("1"),
                 Token::Str("user"),
                 Token::Str("2"),
                 Token::Str("type"),
-                Token::UnitVariant {
-                    name: "WebhookType",
-                    variant: "test",
-                },
-                Token::StructEnd,
+                Token::Str("test"),
+                Token::MapEnd,
             ],
         );
     }
+
+    #[test]
+    fn unknown_fields_and_variants() {
+        let json = r#"{"bot":"1","user":"2","type":"downvote","isWeekend":true,"extra":1}"#;
+        let value: Webhook = serde_json::from_str(json).unwrap();
+        assert_eq!(value.kind, WebhookType::Unknown("downvote".into()));
+        assert_eq!(value.kind.as_str(), "downvote");
+        assert_eq!(value.extra["extra"], 1);
+
+        let json = r#"{"id":"1","username":"foo","certifiedBot":true,"newField":"x"}"#;
+        let bot: Bot = serde_json::from_str(json).unwrap();
+        assert_eq!(bot.id, BotId(1));
+        assert_eq!(bot.username, "foo");
+        assert!(bot.owners.is_empty());
+        assert_eq!(bot.extra.len(), 1);
+        assert_eq!(bot.extra["newField"], "x");
+    }
 }
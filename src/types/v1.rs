@@ -0,0 +1,123 @@
+//! Types of the
+#![cfg_attr(feature = "client", doc = "[v1 API](crate::ApiVersion::V1).")]
+#![cfg_attr(not(feature = "client"), doc = "v1 API.")]
+
+#[cfg(feature = "client")]
+use std::convert::TryFrom;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::UserId;
+#[cfg(feature = "client")]
+use super::{ShardStats, ShardStatsError};
+use crate::timestamp::Timestamp;
+
+/// Information about the project of the token returned by
+#[cfg_attr(
+    feature = "client",
+    doc = "[`Client::project`](crate::Client::project)."
+)]
+#[cfg_attr(not(feature = "client"), doc = "`Client::project`.")]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[non_exhaustive]
+#[serde(default)]
+pub struct Project {
+    pub id: String,
+    pub name: String,
+    pub platform: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub headline: String,
+    pub tags: Vec<String>,
+    pub votes: u64,
+    pub votes_total: u64,
+    pub review_score: f64,
+    pub review_count: u64,
+    /// Unknown fields returned by the API.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Vote for the project.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+#[serde(default)]
+pub struct Vote {
+    /// top.gg id of the user.
+    pub user_id: String,
+    /// Discord id of the user.
+    pub platform_id: UserId,
+    /// Number of votes this vote counts for, e.g. `2` on weekends.
+    pub weight: u64,
+    pub created_at: String,
+    pub expires_at: String,
+    /// Unknown fields returned by the API.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Vote {
+    /// Parses the [`created_at`](Vote::created_at) time of the vote.
+    ///
+    /// Requires the `chrono` or the `time` feature for an implementation of [`Timestamp`].
+    pub fn created_at_as<T: Timestamp>(&self) -> Option<T> {
+        T::parse_rfc3339(&self.created_at)
+    }
+
+    /// Parses the [`expires_at`](Vote::expires_at) time when the user can vote again.
+    ///
+    /// Requires the `chrono` or the `time` feature for an implementation of [`Timestamp`].
+    pub fn expires_at_as<T: Timestamp>(&self) -> Option<T> {
+        T::parse_rfc3339(&self.expires_at)
+    }
+}
+
+/// Page of votes returned by
+#[cfg_attr(
+    feature = "client",
+    doc = "[`Client::project_votes`](crate::Client::project_votes)."
+)]
+#[cfg_attr(not(feature = "client"), doc = "`Client::project_votes`.")]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+#[serde(default)]
+pub struct VotePage {
+    /// Cursor for the next page.
+    pub cursor: Option<String>,
+    pub data: Vec<Vote>,
+    /// Unknown fields returned by the API.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[cfg(feature = "client")]
+#[derive(Serialize)]
+pub(crate) struct Metrics {
+    pub server_count: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shard_count: Option<u64>,
+}
+
+#[cfg(feature = "client")]
+impl TryFrom<ShardStats> for Metrics {
+    type Error = ShardStatsError;
+
+    /// The v1 metrics are the totals of the bot, the count of a single shard is rejected.
+    fn try_from(stats: ShardStats) -> Result<Self, Self::Error> {
+        match stats {
+            ShardStats::Cumulative {
+                server_count,
+                shard_count,
+            } => Ok(Metrics {
+                server_count,
+                shard_count,
+            }),
+            ShardStats::Shard { .. } => Err(ShardStatsError::SingleShard),
+            ShardStats::Shards { shards } => Ok(Metrics {
+                server_count: shards.iter().sum(),
+                shard_count: Some(shards.len() as u64),
+            }),
+        }
+    }
+}
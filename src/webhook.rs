@@ -0,0 +1,268 @@
+//! Verification of incoming webhook requests.
+//!
+//! top.gg authenticates webhook requests either with the legacy scheme, which sends the
+//! plain secret in the `Authorization` header, or by signing the payload with an HMAC-SHA256
+//! signature sent in the [`x-topgg-signature`](SIGNATURE_HEADER) header.
+//!
+//! The signature header has the form `t=<unix timestamp>,v1=<hex signature>`, where the
+//! signature is computed over `<timestamp>.<raw body>`.
+//!
+//! # Example
+//!
+//! ```
+//! use dbl::webhook::Receiver;
+//!
+//! let receiver = Receiver::new().legacy("mywebhook").signed("mysigningsecret");
+//!
+//! let body = br#"{"bot":"1","user":"2","type":"test"}"#;
+//! let hook = receiver.verify(Some("mywebhook"), None, body).unwrap();
+//! assert!(hook.is_test());
+//! ```
+
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use hmac::{Hmac, Mac};
+use serde::de::DeserializeOwned;
+use sha2::Sha256;
+
+use crate::types::Webhook;
+
+pub mod dedup;
+#[cfg(feature = "client")]
+pub mod relay;
+#[cfg(feature = "client")]
+pub mod send;
+
+/// Name of the header containing the payload signature.
+pub const SIGNATURE_HEADER: &str = "x-topgg-signature";
+
+const DEFAULT_TOLERANCE: Duration = Duration::from_secs(5 * 60);
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Errors of the webhook verification.
+#[derive(Debug)]
+pub enum WebhookError {
+    /// No credentials for a configured scheme were sent.
+    MissingCredentials,
+    /// The secret of the `Authorization` header doesn't match.
+    InvalidSecret,
+    /// The signature header is malformed.
+    MalformedSignature,
+    /// The signature doesn't match the payload.
+    InvalidSignature,
+    /// The timestamp of the signature is outside the tolerance window.
+    Expired,
+    /// The payload couldn't be deserialized.
+    Json(serde_json::Error),
+}
+
+impl std::error::Error for WebhookError {}
+
+impl fmt::Display for WebhookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WebhookError::MissingCredentials => f.write_str("Missing webhook credentials"),
+            WebhookError::InvalidSecret => f.write_str("Invalid webhook secret"),
+            WebhookError::MalformedSignature => f.write_str("Malformed webhook signature"),
+            WebhookError::InvalidSignature => f.write_str("Invalid webhook signature"),
+            WebhookError::Expired => f.write_str("Webhook timestamp outside tolerance"),
+            WebhookError::Json(e) => e.fmt(f),
+        }
+    }
+}
+
+/// Verifier for incoming webhook requests.
+///
+/// The legacy and the signed scheme can be enabled at the same time. Requests with a
+/// signature header are verified with the signed scheme if it is enabled.
+#[derive(Clone)]
+pub struct Receiver {
+    secret: Option<String>,
+    signing_secret: Option<Vec<u8>>,
+    tolerance: Duration,
+}
+
+impl Default for Receiver {
+    fn default() -> Self {
+        Receiver::new()
+    }
+}
+
+impl fmt::Debug for Receiver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("legacy", &self.secret.is_some())
+            .field("signed", &self.signing_secret.is_some())
+            .field("tolerance", &self.tolerance)
+            .finish()
+    }
+}
+
+impl Receiver {
+    /// Constructs a new `Receiver` without any enabled scheme.
+    pub fn new() -> Self {
+        Receiver {
+            secret: None,
+            signing_secret: None,
+            tolerance: DEFAULT_TOLERANCE,
+        }
+    }
+
+    /// Enables the legacy scheme with the plain secret in the `Authorization` header.
+    pub fn legacy<T: Into<String>>(mut self, secret: T) -> Self {
+        self.secret = Some(secret.into());
+        self
+    }
+
+    /// Enables the signed scheme with the HMAC signature in the
+    /// [`x-topgg-signature`](SIGNATURE_HEADER) header.
+    pub fn signed<T: AsRef<[u8]>>(mut self, secret: T) -> Self {
+        self.signing_secret = Some(secret.as_ref().to_vec());
+        self
+    }
+
+    /// Sets the maximum age of signed requests for replay protection. Defaults to 5 minutes.
+    pub fn tolerance(mut self, tolerance: Duration) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Verifies the request and deserializes the body into a [`Webhook`].
+    ///
+    /// `authorization` and `signature` are the values of the `Authorization` and the
+    /// [`x-topgg-signature`](SIGNATURE_HEADER) headers.
+    pub fn verify(
+        &self,
+        authorization: Option<&str>,
+        signature: Option<&str>,
+        body: &[u8],
+    ) -> Result<Webhook, WebhookError> {
+        self.verify_as(authorization, signature, body)
+    }
+
+    /// Verifies the request and deserializes the body into `T`.
+    pub fn verify_as<T>(
+        &self,
+        authorization: Option<&str>,
+        signature: Option<&str>,
+        body: &[u8],
+    ) -> Result<T, WebhookError>
+    where
+        T: DeserializeOwned,
+    {
+        self.verify_at(authorization, signature, body, SystemTime::now())?;
+        serde_json::from_slice(body).map_err(WebhookError::Json)
+    }
+
+    fn verify_at(
+        &self,
+        authorization: Option<&str>,
+        signature: Option<&str>,
+        body: &[u8],
+        now: SystemTime,
+    ) -> Result<(), WebhookError> {
+        match (&self.signing_secret, signature, &self.secret, authorization) {
+            (Some(key), Some(signature), _, _) => {
+                let (timestamp, signature) = parse_signature(signature)?;
+                // Compare plain seconds, the timestamp isn't verified yet and may overflow.
+                let now = now.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+                if now.abs_diff(timestamp) > self.tolerance.as_secs() {
+                    return Err(WebhookError::Expired);
+                }
+                let mut mac = HmacSha256::new_from_slice(key).expect("any key length");
+                mac.update(timestamp.to_string().as_bytes());
+                mac.update(b".");
+                mac.update(body);
+                mac.verify_slice(&signature)
+                    .map_err(|_| WebhookError::InvalidSignature)
+            }
+            (_, _, Some(secret), Some(authorization)) => {
+                if constant_time_eq(secret.as_bytes(), authorization.as_bytes()) {
+                    Ok(())
+                } else {
+                    Err(WebhookError::InvalidSecret)
+                }
+            }
+            _ => Err(WebhookError::MissingCredentials),
+        }
+    }
+}
+
+/// Computes the value of the [`x-topgg-signature`](SIGNATURE_HEADER) header for a payload.
+pub fn sign(secret: &[u8], timestamp: u64, body: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    let signature = hex::encode(mac.finalize().into_bytes());
+    format!("t={},v1={}", timestamp, signature)
+}
+
+fn parse_signature(value: &str) -> Result<(u64, Vec<u8>), WebhookError> {
+    let mut timestamp = None;
+    let mut signature = None;
+    for part in value.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", t)) => timestamp = t.parse().ok(),
+            Some(("v1", s)) => signature = hex::decode(s).ok(),
+            _ => {}
+        }
+    }
+    match (timestamp, signature) {
+        (Some(t), Some(s)) => Ok((t, s)),
+        _ => Err(WebhookError::MalformedSignature),
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &[u8] = br#"{"bot":"1","user":"2","type":"upvote","isWeekend":true}"#;
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn signed_scheme() {
+        let receiver = Receiver::new().signed("secret");
+        let signature = sign(b"secret", 1000, BODY);
+
+        let res = receiver.verify_at(None, Some(&signature), BODY, at(1100));
+        assert!(res.is_ok());
+
+        let res = receiver.verify_at(None, Some(&signature), b"{}", at(1100));
+        assert!(matches!(res, Err(WebhookError::InvalidSignature)));
+
+        let res = receiver.verify_at(None, Some(&signature), BODY, at(2000));
+        assert!(matches!(res, Err(WebhookError::Expired)));
+
+        let signature = "t=18446744073709551615,v1=00";
+        let res = receiver.verify_at(None, Some(signature), BODY, at(1100));
+        assert!(matches!(res, Err(WebhookError::Expired)));
+
+        let res = receiver.verify_at(None, Some("v1=abc"), BODY, at(1100));
+        assert!(matches!(res, Err(WebhookError::MalformedSignature)));
+
+        let res = receiver.verify_at(Some("secret"), None, BODY, at(1100));
+        assert!(matches!(res, Err(WebhookError::MissingCredentials)));
+    }
+
+    #[test]
+    fn legacy_scheme() {
+        let receiver = Receiver::new().legacy("secret").signed("signing");
+
+        let hook = receiver.verify(Some("secret"), None, BODY).unwrap();
+        assert!(hook.is_weekend);
+
+        let res = receiver.verify(Some("wrong"), None, BODY);
+        assert!(matches!(res, Err(WebhookError::InvalidSecret)));
+    }
+}
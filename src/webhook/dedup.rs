@@ -0,0 +1,157 @@
+//! Deduplication of retried webhook deliveries.
+//!
+//! top.gg retries webhook deliveries if the endpoint doesn't respond in time, which can lead
+//! to the same vote being handled twice. The [`Deduplicator`] derives an idempotency key from
+//! the bot, the user, the type of the vote and the arrival time window and drops deliveries
+//! with an already seen key.
+//!
+//! # Example
+//!
+//! ```
+//! use dbl::types::{Webhook, WebhookType};
+//! use dbl::webhook::dedup::Deduplicator;
+//!
+//! let dedup = Deduplicator::new();
+//! let hook = Webhook::new(1, 2, WebhookType::Upvote);
+//!
+//! assert_eq!(dedup.handle(hook.clone(), |hook| hook.user), Some(2.into()));
+//! assert_eq!(dedup.handle(hook, |hook| hook.user), None);
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::types::Webhook;
+
+const DEFAULT_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// Storage for idempotency keys with a time to live.
+pub trait KeyStore: Send + Sync {
+    /// Returns `true` if the key is stored and not expired.
+    fn contains(&self, key: &str) -> bool;
+
+    /// Stores the key for the duration of `ttl`.
+    ///
+    /// Returns `false` if the key was already stored and not expired.
+    fn insert(&self, key: String, ttl: Duration) -> bool;
+}
+
+/// In-memory [`KeyStore`]. Expired keys are removed on insert.
+#[derive(Debug, Default)]
+pub struct MemoryKeyStore(Mutex<HashMap<String, Instant>>);
+
+impl MemoryKeyStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl KeyStore for MemoryKeyStore {
+    fn contains(&self, key: &str) -> bool {
+        let keys = self.0.lock().unwrap();
+        keys.get(key).is_some_and(|exp| *exp > Instant::now())
+    }
+
+    fn insert(&self, key: String, ttl: Duration) -> bool {
+        let now = Instant::now();
+        let mut keys = self.0.lock().unwrap();
+        keys.retain(|_, exp| *exp > now);
+        if keys.contains_key(&key) {
+            return false;
+        }
+        keys.insert(key, now + ttl);
+        true
+    }
+}
+
+/// Idempotency layer for webhook handling.
+///
+/// Deliveries of the same vote arriving within the same or the adjacent time window are
+/// considered duplicates.
+pub struct Deduplicator<S = MemoryKeyStore> {
+    store: S,
+    window: Duration,
+}
+
+impl Default for Deduplicator {
+    fn default() -> Self {
+        Deduplicator::new()
+    }
+}
+
+impl Deduplicator {
+    /// Constructs a new `Deduplicator` with an in-memory store and a window of 10 minutes.
+    pub fn new() -> Self {
+        Deduplicator::with_store(MemoryKeyStore::new())
+    }
+}
+
+impl<S: KeyStore> Deduplicator<S> {
+    /// Constructs a new `Deduplicator` with a custom store.
+    pub fn with_store(store: S) -> Self {
+        Deduplicator {
+            store,
+            window: DEFAULT_WINDOW,
+        }
+    }
+
+    /// Sets the arrival time window. Defaults to 10 minutes.
+    pub fn window(mut self, window: Duration) -> Self {
+        self.window = window.max(Duration::from_secs(1));
+        self
+    }
+
+    /// Records the delivery and returns `true` if it's the first delivery of the vote.
+    pub fn check(&self, hook: &Webhook) -> bool {
+        self.check_at(hook, SystemTime::now())
+    }
+
+    /// Calls `handler` with the webhook unless it's a duplicate delivery.
+    pub fn handle<F, R>(&self, hook: Webhook, handler: F) -> Option<R>
+    where
+        F: FnOnce(Webhook) -> R,
+    {
+        if self.check(&hook) {
+            Some(handler(hook))
+        } else {
+            None
+        }
+    }
+
+    fn check_at(&self, hook: &Webhook, now: SystemTime) -> bool {
+        let secs = now.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let bucket = secs.as_secs() / self.window.as_secs();
+
+        if bucket > 0 && self.store.contains(&key(hook, bucket - 1)) {
+            return false;
+        }
+        self.store.insert(key(hook, bucket), self.window * 2)
+    }
+}
+
+fn key(hook: &Webhook, bucket: u64) -> String {
+    let kind = hook.kind.as_str();
+    format!("{}:{}:{}:{}", hook.bot, hook.user, kind, bucket)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::WebhookType;
+
+    #[test]
+    fn duplicates_within_window() {
+        let dedup = Deduplicator::new().window(Duration::from_secs(60));
+        let mut hook = Webhook::new(1, 2, WebhookType::Upvote);
+        let at = |secs| UNIX_EPOCH + Duration::from_secs(secs);
+
+        assert!(dedup.check_at(&hook, at(110)));
+        assert!(!dedup.check_at(&hook, at(119)));
+        assert!(!dedup.check_at(&hook, at(130)));
+        assert!(dedup.check_at(&hook, at(300)));
+
+        hook.user = 3.into();
+        assert!(dedup.check_at(&hook, at(300)));
+    }
+}
@@ -0,0 +1,254 @@
+//! Forwarding of votes to Discord channel webhooks.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use dbl::webhook::relay::DiscordRelay;
+//!
+//! # async fn run(hook: dbl::types::Webhook) -> Result<(), dbl::Error> {
+//! let relay = DiscordRelay::new("https://discord.com/api/webhooks/1234/token")
+//!     .template("{user} voted for us!{weekend}")
+//!     .weekend_tag(" (counts double)");
+//!
+//! relay.relay(&hook).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Client as ReqwestClient, StatusCode};
+use serde::{Deserialize, Serialize};
+use tokio::time::{self, Instant};
+
+use crate::error;
+use crate::types::Webhook;
+use crate::Error;
+
+const DEFAULT_TEMPLATE: &str = "{user} voted for <@{bot}>!{weekend}";
+const DEFAULT_WEEKEND_TAG: &str = " (weekend vote)";
+const DEFAULT_MAX_RETRIES: u32 = 3;
+
+/// Relay that posts a templated message for every vote to a Discord webhook.
+///
+/// The template supports the following placeholders:
+///
+/// - `{user}`: mention of the voting user
+/// - `{user_id}`: id of the voting user
+/// - `{bot}`: id of the bot
+/// - `{weekend}`: the weekend tag for weekend votes, empty otherwise
+/// - `{query}`: the raw query string of the vote link, empty if none
+///
+/// Failed requests are retried with exponential backoff and Discord's rate limit is obeyed.
+pub struct DiscordRelay {
+    client: ReqwestClient,
+    url: String,
+    template: String,
+    weekend_tag: String,
+    max_retries: u32,
+    blocked_until: Mutex<Option<Instant>>,
+}
+
+#[derive(Serialize)]
+struct Message<'a> {
+    content: &'a str,
+    allowed_mentions: AllowedMentions,
+}
+
+#[derive(Serialize)]
+struct AllowedMentions {
+    users: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct DiscordRatelimit {
+    retry_after: f64,
+}
+
+impl DiscordRelay {
+    /// Constructs a new `DiscordRelay` for the Discord webhook `url`.
+    pub fn new<T: Into<String>>(url: T) -> Self {
+        DiscordRelay::new_with_client(ReqwestClient::new(), url)
+    }
+
+    /// Constructs a new `DiscordRelay` with a `reqwest` client.
+    pub fn new_with_client<T: Into<String>>(client: ReqwestClient, url: T) -> Self {
+        DiscordRelay {
+            client,
+            url: url.into(),
+            template: DEFAULT_TEMPLATE.to_string(),
+            weekend_tag: DEFAULT_WEEKEND_TAG.to_string(),
+            max_retries: DEFAULT_MAX_RETRIES,
+            blocked_until: Mutex::new(None),
+        }
+    }
+
+    /// Sets the message template. Defaults to `{user} voted for <@{bot}>!{weekend}`.
+    pub fn template<T: Into<String>>(mut self, template: T) -> Self {
+        self.template = template.into();
+        self
+    }
+
+    /// Sets the text of the `{weekend}` placeholder for weekend votes.
+    pub fn weekend_tag<T: Into<String>>(mut self, tag: T) -> Self {
+        self.weekend_tag = tag.into();
+        self
+    }
+
+    /// Sets the maximum number of retries of a failed request. Defaults to `3`.
+    pub fn max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    /// Renders the message for a vote.
+    pub fn render(&self, hook: &Webhook) -> String {
+        let weekend = if hook.is_weekend {
+            &*self.weekend_tag
+        } else {
+            ""
+        };
+        self.template
+            .replace("{user}", &format!("<@{}>", hook.user))
+            .replace("{user_id}", &hook.user.to_string())
+            .replace("{bot}", &hook.bot.to_string())
+            .replace("{weekend}", weekend)
+            .replace("{query}", hook.query.as_deref().unwrap_or_default())
+    }
+
+    /// Posts the message for a vote to the Discord webhook.
+    pub async fn relay(&self, hook: &Webhook) -> Result<(), Error> {
+        let content = self.render(hook);
+        let message = Message {
+            content: &content,
+            allowed_mentions: AllowedMentions {
+                users: vec![hook.user.to_string()],
+            },
+        };
+
+        let mut attempt = 0;
+        loop {
+            self.wait_for_ratelimit().await;
+
+            let resp = self.client.post(&self.url).json(&message).send().await;
+            let retry = match resp {
+                Ok(resp) => {
+                    self.update_ratelimit(resp.headers());
+                    match resp.status() {
+                        StatusCode::TOO_MANY_REQUESTS => {
+                            let header = retry_after(resp.headers());
+                            let body = resp.json::<DiscordRatelimit>().await.ok();
+                            let wait = body
+                                .and_then(|rl| Duration::try_from_secs_f64(rl.retry_after).ok())
+                                .or(header)
+                                .unwrap_or_else(|| backoff(attempt));
+                            if attempt >= self.max_retries {
+                                return Err(error::ratelimit(wait.as_secs() as u32));
+                            }
+                            wait
+                        }
+                        status if status.is_server_error() && attempt < self.max_retries => {
+                            backoff(attempt)
+                        }
+                        _ => return resp.error_for_status().map(|_| ()).map_err(error::from),
+                    }
+                }
+                Err(e) if attempt < self.max_retries && !e.is_builder() => backoff(attempt),
+                Err(e) => return Err(error::from(e)),
+            };
+            attempt += 1;
+            time::sleep(retry).await;
+        }
+    }
+
+    async fn wait_for_ratelimit(&self) {
+        let blocked_until = *self.blocked_until.lock().unwrap();
+        if let Some(until) = blocked_until {
+            time::sleep_until(until).await;
+        }
+    }
+
+    fn update_ratelimit(&self, headers: &HeaderMap) {
+        let header = |name| headers.get(name).and_then(|v| v.to_str().ok());
+        let remaining = header("x-ratelimit-remaining");
+        let reset_after = header("x-ratelimit-reset-after")
+            .and_then(|v| v.parse::<f64>().ok())
+            .and_then(|v| Duration::try_from_secs_f64(v).ok());
+
+        let mut blocked_until = self.blocked_until.lock().unwrap();
+        *blocked_until = match (remaining, reset_after) {
+            (Some("0"), Some(reset)) => Instant::now().checked_add(reset),
+            _ => None,
+        };
+    }
+}
+
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?;
+    let secs = value.parse::<f64>().ok()?;
+    Duration::try_from_secs_f64(secs).ok()
+}
+
+fn backoff(attempt: u32) -> Duration {
+    Duration::from_millis(500 << attempt.min(6))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use crate::types::WebhookType;
+
+    fn hook() -> Webhook {
+        let mut hook = Webhook::new(1, 2, WebhookType::Upvote);
+        hook.is_weekend = true;
+        hook.query = Some("?ref=home".into());
+        hook
+    }
+
+    #[test]
+    fn render_template() {
+        let relay = DiscordRelay::new("http://localhost")
+            .template("{user} ({user_id}) voted for {bot}{weekend} {query}")
+            .weekend_tag(" x2");
+        assert_eq!(relay.render(&hook()), "<@2> (2) voted for 1 x2 ?ref=home");
+    }
+
+    #[tokio::test]
+    async fn invalid_ratelimit_values() {
+        let (port, server) = test_util::serve(vec![
+            (
+                "429 Too Many Requests\r\nretry-after: NaN",
+                r#"{"retry_after":-1.0}"#,
+            ),
+            (
+                "204 No Content\r\nx-ratelimit-remaining: 0\r\nx-ratelimit-reset-after: 1e300",
+                "",
+            ),
+        ]);
+        let relay = DiscordRelay::new(format!("http://127.0.0.1:{}/webhook", port));
+
+        relay.relay(&hook()).await.unwrap();
+        assert_eq!(*relay.blocked_until.lock().unwrap(), None);
+        assert_eq!(server.join().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn retry_on_ratelimit() {
+        let (port, server) = test_util::serve(vec![
+            ("429 Too Many Requests", r#"{"retry_after":0.01}"#),
+            ("204 No Content", ""),
+        ]);
+        let relay = DiscordRelay::new(format!("http://127.0.0.1:{}/webhook", port));
+
+        relay.relay(&hook()).await.unwrap();
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 2);
+        let message: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+        assert_eq!(message["content"], "<@2> voted for <@1>! (weekend vote)");
+        assert_eq!(message["allowed_mentions"]["users"][0], "2");
+    }
+}
@@ -0,0 +1,163 @@
+//! Sending of webhook votes for testing webhook receivers.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use dbl::types::{Webhook, WebhookType};
+//! use dbl::webhook::send::Sender;
+//!
+//! # async fn run() -> Result<(), dbl::Error> {
+//! let sender = Sender::new("http://localhost:3030/dbl/webhook").secret("mywebhook");
+//! let hook = Webhook::new(1234, 2345, WebhookType::Test);
+//!
+//! let status = sender.send(&hook).await?;
+//! println!("{}", status);
+//!
+//! let report = sender.send_many(&hook, 100, 10).await;
+//! println!("{} of 100 succeeded", report.succeeded);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures_util::stream::{self, StreamExt};
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+use reqwest::{Client as ReqwestClient, StatusCode};
+use tokio::time::Instant;
+
+use super::{sign, SIGNATURE_HEADER};
+use crate::error;
+use crate::types::Webhook;
+use crate::Error;
+
+/// Authentication used by the [`Sender`].
+#[derive(Clone, Debug)]
+enum Auth {
+    None,
+    Secret(String),
+    Signed(Vec<u8>),
+}
+
+/// Sender of webhook votes to a webhook receiver.
+#[derive(Clone, Debug)]
+pub struct Sender {
+    client: ReqwestClient,
+    url: String,
+    auth: Auth,
+}
+
+/// Result of [`Sender::send_many`].
+#[derive(Debug)]
+pub struct LoadReport {
+    /// Number of deliveries with a successful response code.
+    pub succeeded: usize,
+    /// Errors of the failed deliveries.
+    pub failed: Vec<Error>,
+    /// Time it took to send all deliveries.
+    pub elapsed: Duration,
+}
+
+impl Sender {
+    /// Constructs a new `Sender` for the webhook receiver at `url`.
+    pub fn new<T: Into<String>>(url: T) -> Self {
+        Sender::new_with_client(ReqwestClient::new(), url)
+    }
+
+    /// Constructs a new `Sender` with a `reqwest` client.
+    pub fn new_with_client<T: Into<String>>(client: ReqwestClient, url: T) -> Self {
+        Sender {
+            client,
+            url: url.into(),
+            auth: Auth::None,
+        }
+    }
+
+    /// Sends the plain secret in the `Authorization` header.
+    pub fn secret<T: Into<String>>(mut self, secret: T) -> Self {
+        self.auth = Auth::Secret(secret.into());
+        self
+    }
+
+    /// Signs the payload and sends the signature in the
+    /// [`x-topgg-signature`](super::SIGNATURE_HEADER) header.
+    pub fn signed<T: AsRef<[u8]>>(mut self, secret: T) -> Self {
+        self.auth = Auth::Signed(secret.as_ref().to_vec());
+        self
+    }
+
+    /// Sends the webhook and returns the response code.
+    ///
+    /// Returns an error if the receiver doesn't respond with a successful response code.
+    pub async fn send(&self, hook: &Webhook) -> Result<StatusCode, Error> {
+        let body = serde_json::to_vec(hook).map_err(Error::Json)?;
+        let mut req = self
+            .client
+            .post(&self.url)
+            .header(CONTENT_TYPE, "application/json");
+
+        match &self.auth {
+            Auth::None => {}
+            Auth::Secret(secret) => req = req.header(AUTHORIZATION, secret),
+            Auth::Signed(secret) => {
+                let now = SystemTime::now().duration_since(UNIX_EPOCH);
+                let timestamp = now.unwrap_or_default().as_secs();
+                req = req.header(SIGNATURE_HEADER, sign(secret, timestamp, &body));
+            }
+        }
+
+        let resp = req.body(body).send().await.map_err(error::from)?;
+        let resp = resp.error_for_status().map_err(error::from)?;
+        Ok(resp.status())
+    }
+
+    /// Sends the webhook `count` times with at most `concurrency` deliveries in flight.
+    pub async fn send_many(&self, hook: &Webhook, count: usize, concurrency: usize) -> LoadReport {
+        let start = Instant::now();
+        let results = stream::iter(0..count)
+            .map(|_| self.send(hook))
+            .buffer_unordered(concurrency.max(1))
+            .collect::<Vec<_>>()
+            .await;
+
+        let mut report = LoadReport {
+            succeeded: 0,
+            failed: Vec::new(),
+            elapsed: start.elapsed(),
+        };
+        for result in results {
+            match result {
+                Ok(_) => report.succeeded += 1,
+                Err(e) => report.failed.push(e),
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use crate::types::WebhookType;
+    use crate::webhook::Receiver;
+
+    #[tokio::test]
+    async fn send_signed() {
+        let (port, server) =
+            test_util::serve(vec![("204 No Content", ""), ("401 Unauthorized", "")]);
+        let sender = Sender::new(format!("http://127.0.0.1:{}/", port)).signed("secret");
+        let hook = Webhook::new(1, 2, WebhookType::Test);
+
+        let status = sender.send(&hook).await.unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let err = sender.send(&hook).await.unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::UNAUTHORIZED));
+
+        let requests = server.join().unwrap();
+        let signature = requests[0].header(SIGNATURE_HEADER);
+        let receiver = Receiver::new().signed("secret");
+        let received = receiver.verify(None, signature, &requests[0].body);
+        assert_eq!(received.unwrap(), hook);
+    }
+}
@@ -0,0 +1,183 @@
+//! Cached weekend status.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::Mutex as AsyncMutex;
+use tokio::time::{self, Instant, MissedTickBehavior};
+
+use crate::{Client, Error};
+
+const DEFAULT_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Cached result of [`Client::is_weekend`].
+///
+/// The status is refreshed lazily by [`is_weekend`](WeekendCache::is_weekend) when it's older
+/// than the configured TTL. Use [`run`](WeekendCache::run) to refresh the status periodically
+/// in the background instead, so that `is_weekend` never waits for the API.
+/// Cloned instances share the same cache.
+///
+/// # Example
+///
+/// ```no_run
+/// use dbl::weekend::WeekendCache;
+/// use dbl::Client;
+///
+/// # async fn run() -> Result<(), dbl::Error> {
+/// let client = Client::new("token".into())?;
+/// let weekend = WeekendCache::new(client);
+///
+/// if weekend.is_weekend().await? {
+///     println!("Votes count double now!");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct WeekendCache {
+    client: Client,
+    ttl: Duration,
+    state: Arc<Mutex<Option<(bool, Instant)>>>,
+    refresh: Arc<AsyncMutex<()>>,
+}
+
+impl WeekendCache {
+    /// Constructs a new `WeekendCache` with a TTL of 5 minutes.
+    pub fn new(client: Client) -> Self {
+        WeekendCache {
+            client,
+            ttl: DEFAULT_TTL,
+            state: Arc::new(Mutex::new(None)),
+            refresh: Arc::new(AsyncMutex::new(())),
+        }
+    }
+
+    /// Sets the time after which the status is refreshed.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Returns the weekend status, refreshing it if the cached value is stale.
+    pub async fn is_weekend(&self) -> Result<bool, Error> {
+        if let Some(weekend) = self.fresh() {
+            return Ok(weekend);
+        }
+        let _guard = self.refresh.lock().await;
+        // Another task may have refreshed the status while waiting for the lock.
+        match self.fresh() {
+            Some(weekend) => Ok(weekend),
+            None => self.fetch().await,
+        }
+    }
+
+    /// Returns the last fetched weekend status without calling the API.
+    pub fn cached(&self) -> Option<bool> {
+        let state = self.state.lock().unwrap();
+        state.map(|(weekend, _)| weekend)
+    }
+
+    /// Refreshes the weekend status from the API.
+    pub async fn refresh(&self) -> Result<bool, Error> {
+        let _guard = self.refresh.lock().await;
+        self.fetch().await
+    }
+
+    /// Refreshes the status immediately and then every TTL.
+    ///
+    /// The callback is called with the result of every refresh. On errors the previous status
+    /// is kept until the next refresh.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dbl::weekend::WeekendCache;
+    /// # fn run(weekend: WeekendCache) {
+    /// let cache = weekend.clone();
+    /// tokio::spawn(async move {
+    ///     cache
+    ///         .run(|result| {
+    ///             if let Err(e) = result {
+    ///                 eprintln!("failed to refresh weekend status: {}", e);
+    ///             }
+    ///         })
+    ///         .await
+    /// });
+    /// # }
+    /// ```
+    pub async fn run<F>(&self, mut callback: F)
+    where
+        F: FnMut(Result<bool, Error>),
+    {
+        let mut interval = time::interval(self.ttl);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            callback(self.refresh().await);
+        }
+    }
+
+    fn fresh(&self) -> Option<bool> {
+        let state = self.state.lock().unwrap();
+        match *state {
+            Some((weekend, fetched)) if fetched.elapsed() < self.ttl => Some(weekend),
+            _ => None,
+        }
+    }
+
+    async fn fetch(&self) -> Result<bool, Error> {
+        let weekend = self.client.is_weekend().await?;
+        *self.state.lock().unwrap() = Some((weekend, Instant::now()));
+        Ok(weekend)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, Redirect};
+
+    fn client(port: u16) -> Client {
+        Client::new("token".into())
+            .unwrap()
+            .with_hook(Redirect(port))
+    }
+
+    #[tokio::test]
+    async fn cached_within_ttl() {
+        let (port, server) = test_util::serve(vec![
+            ("200 OK", r#"{"is_weekend":true}"#),
+            ("200 OK", r#"{"is_weekend":false}"#),
+        ]);
+        let weekend = WeekendCache::new(client(port));
+
+        assert_eq!(weekend.cached(), None);
+        assert!(weekend.is_weekend().await.unwrap());
+        assert!(weekend.is_weekend().await.unwrap());
+        assert!(!weekend.refresh().await.unwrap());
+        assert!(!weekend.is_weekend().await.unwrap());
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].request_line, "GET /api/weekend HTTP/1.1");
+    }
+
+    #[tokio::test]
+    async fn run_refreshes_periodically() {
+        let (port, server) = test_util::serve(vec![
+            ("200 OK", r#"{"is_weekend":false}"#),
+            ("200 OK", r#"{"is_weekend":true}"#),
+        ]);
+        let weekend = WeekendCache::new(client(port)).ttl(Duration::from_millis(10));
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let cache = weekend.clone();
+        let task = tokio::spawn(async move { cache.run(|res| tx.send(res).unwrap()).await });
+        assert!(!rx.recv().await.unwrap().unwrap());
+        assert!(rx.recv().await.unwrap().unwrap());
+        task.abort();
+        assert_eq!(server.join().unwrap().len(), 2);
+
+        assert_eq!(weekend.cached(), Some(true));
+    }
+}